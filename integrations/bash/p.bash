@@ -1,7 +1,17 @@
 #!/bin/bash
 
+# Fills the caller's local 'root_args' array, so paths with spaces stay one word
+p_root_args() {
+    root_args=()
+    if [ -n "$PROJECT_HOME" ]; then
+        root_args=(--projects-root "$PROJECT_HOME")
+    fi
+}
+
 p_cd_completions() {
-    project-manager list-projects --projects-root $PROJECT_HOME
+    local -a root_args
+    p_root_args
+    project-manager cd --complete "$1" "${root_args[@]}" 2>/dev/null
     #"Switch to project" | awk '{sub(rpl, "", $1); print $1}' rpl="$PROJECT_HOME/" | awk '{sub("/(?:.(?!/))*$", "", $0); print $0}'
 }
 
@@ -35,6 +45,10 @@ _p_completions() {
     COMREPLY=()
     cur="${COMP_WORDS[COMP_CWORD]}"
     prev="${COMP_WORDS[COMP_CWORD-1]}"
+    # Don't split sub-projects like 'repo:crate' at the ':'
+    if declare -F _get_comp_words_by_ref >/dev/null; then
+        _get_comp_words_by_ref -n : cur prev
+    fi
     opts="--help --projects-root --version"
 
     if [[ ${cur} == -* ]]; then
@@ -49,8 +63,16 @@ _p_completions() {
     fi
 
     if [ "$prev" == "cd" ]; then
-        projects=$(project-manager list-projects --projects-root $PROJECT_HOME)
+        projects=$(p_cd_completions "$cur")
         COMPREPLY=( $(compgen -W "$projects" -- ${cur}) )
+        # Keep completing subdirectories without a space
+        if [[ "${COMPREPLY[0]}" == */ ]]; then
+            compopt -o nospace
+        fi
+        # Sub-projects contain ':', which is a word break for readline
+        if declare -F __ltrim_colon_completions >/dev/null; then
+            __ltrim_colon_completions "$cur"
+        fi
         return 0
     fi
 
@@ -58,6 +80,20 @@ _p_completions() {
 
 complete -F _p_completions p
 
+# Opt-in prompt segment: call p_enable_prompt and reference $P_PROMPT in PS1
+_p_prompt_update() {
+    local -a root_args
+    p_root_args
+    P_PROMPT=$(project-manager prompt "${root_args[@]}" 2>/dev/null)
+}
+
+p_enable_prompt() {
+    case ";$PROMPT_COMMAND;" in
+        *";_p_prompt_update;"*) ;;
+        *) PROMPT_COMMAND="_p_prompt_update${PROMPT_COMMAND:+;$PROMPT_COMMAND}" ;;
+    esac
+}
+
 supported_versions=">=0.2.0, <0.3.0"
 
 p() {
@@ -66,9 +102,11 @@ p() {
         return 1
     fi
 
+    local -a root_args
+    p_root_args
     if [ \( -n "$1" \) -a \( "$1" == "cd" \) ]; then
-        cd $(project-manager cd --projects-root "$PROJECT_HOME" "$2");
+        cd "$(project-manager cd "${root_args[@]}" "$2")";
     else
-        project-manager "$@" --projects-root "$PROJECT_HOME";
+        project-manager "$@" "${root_args[@]}";
     fi
 }
\ No newline at end of file
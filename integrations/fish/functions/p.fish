@@ -1,5 +1,12 @@
+function p_root_args
+    if set -q PROJECT_HOME; and test -n "$PROJECT_HOME"
+        echo --projects-root
+        echo $PROJECT_HOME
+    end
+end
+
 function p_cd_completions
-    project-manager --projects-root $PROJECT_HOME list-projects
+    project-manager cd --complete (commandline -ct) (p_root_args) 2>/dev/null
     #"Switch to project" | awk '{sub(rpl, "", $1); print $1}' rpl="$PROJECT_HOME/" | awk '{sub("/(?:.(?!/))*$", "", $0); print $0}'
 end
 
@@ -33,10 +40,15 @@ end
 
 p_completions
 
+# Opt-in prompt segment, e.g. `function fish_right_prompt; p_prompt; end`
+function p_prompt
+    project-manager prompt (p_root_args) 2>/dev/null
+end
+
 function p
     if test \( -n $argv[1] \) -a \( $argv[1] = "cd" \)
-        cd (project-manager --projects-root $PROJECT_HOME cd $argv[2])
+        cd (project-manager cd $argv[2] (p_root_args))
     else
-        project-manager --projects-root $PROJECT_HOME $argv
+        project-manager $argv (p_root_args)
     end
 end
@@ -15,6 +15,14 @@ pub enum Commands {
     New(NewArgs),
     ListCommands,
     SupportedVersion(SupportedVersionArgs),
+    Reindex(ReindexArgs),
+    Tree(TreeArgs),
+    Info(InfoArgs),
+    Check(CheckArgs),
+    Alias(AliasArgs),
+    #[command(visible_alias = "current")]
+    Where(WhereArgs),
+    Prompt(PromptArgs),
 }
 
 impl Commands {
@@ -25,15 +33,222 @@ impl Commands {
         "new",
         "list-commands",
         "supported-version",
+        "reindex",
+        "tree",
+        "info",
+        "check",
+        "alias",
+        "where",
+        "prompt",
     ];
 }
 
 #[derive(Args)]
-#[command(author, version, about="List all available projects", long_about=None)]
-pub struct ListProjectsArgs {
+pub struct DetectorArgs {
     /// Directory where projects are stored
     #[arg(short, long = "projects-root")]
-    pub project_dir_path: std::path::PathBuf,
+    pub project_dir_path: Option<std::path::PathBuf>,
+
+    /// Additional projects root listed below PREFIX, may be repeated
+    #[arg(long = "mount", value_name = "PREFIX=PATH")]
+    pub mounts: Vec<crate::project::ProjectRoot>,
+
+    /// Skip hidden files and directories [default: true]
+    #[arg(long = "ignore-hidden", value_name = "BOOL")]
+    pub ignore_hidden: Option<bool>,
+
+    /// How symlinked directories are treated [default: follow]
+    #[arg(long = "symlinks", value_name = "POLICY")]
+    pub symlinks: Option<crate::project::SymlinkPolicy>,
+
+    /// Only scan directories up to this many levels below the projects root
+    #[arg(long = "max-depth", value_name = "DEPTH")]
+    pub max_depth: Option<usize>,
+
+    /// List workspace members as sub-projects, e.g. 'repo:crate' [default: false]
+    #[arg(long = "sub-projects", value_name = "BOOL")]
+    pub sub_projects: Option<bool>,
+
+    /// List checked out git submodules as sub-projects [default: false]
+    #[arg(long = "submodules", value_name = "BOOL")]
+    pub submodules: Option<bool>,
+
+    /// Skip directory entries matching pattern, in addition to the configured ones
+    #[arg(long = "ignore", value_name = "PATTERN")]
+    pub ignore_patterns: Vec<String>,
+
+    /// Scan the projects root again instead of using the cached index
+    #[arg(long = "refresh")]
+    pub refresh: bool,
+
+    /// Print paths that were skipped because they could not be read
+    #[arg(short, long = "verbose")]
+    pub verbose: bool,
+}
+
+#[derive(Args)]
+#[command(author, version, about="Show details of a project", long_about=None)]
+pub struct InfoArgs {
+    /// Project to describe, matched like the argument of cd
+    pub project_name: String,
+
+    /// Print the project as JSON object
+    #[arg(long = "json")]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub detector: DetectorArgs,
+}
+
+#[derive(Args)]
+#[command(author, version, about="Show which project a directory belongs to", long_about=None)]
+pub struct WhereArgs {
+    /// Directory or file to look up [default: current directory]
+    pub path: Option<std::path::PathBuf>,
+
+    /// Print the location as JSON object
+    #[arg(long = "json")]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub detector: DetectorArgs,
+}
+
+#[derive(Args)]
+#[command(author, version, about="Print the current project for a shell prompt", long_about=None)]
+pub struct PromptArgs {
+    /// Format of the segment, with the placeholders of list-projects --template
+    /// [default: "{slug} ({kinds})"]
+    #[arg(long = "template", value_name = "TEMPLATE")]
+    pub template: Option<crate::output::format::Template>,
+
+    /// Print the segment without trailing newline, for a starship custom module
+    #[arg(long = "starship")]
+    pub starship: bool,
+
+    #[command(flatten)]
+    pub detector: DetectorArgs,
+}
+
+#[derive(Args)]
+#[command(author, version, about="List directories that were given the same slug", long_about=None)]
+pub struct CheckArgs {
+    #[command(flatten)]
+    pub detector: DetectorArgs,
+}
+
+#[derive(Args)]
+#[command(author, version, about="Manage short names for projects", long_about=None)]
+pub struct AliasArgs {
+    #[command(subcommand)]
+    pub command: AliasCommands,
+}
+
+#[derive(Subcommand)]
+pub enum AliasCommands {
+    Add(AliasAddArgs),
+    Remove(AliasRemoveArgs),
+    List(AliasListArgs),
+}
+
+#[derive(Args)]
+#[command(author, version, about="Add an alias for a project", long_about=None)]
+pub struct AliasAddArgs {
+    /// Name of the alias
+    pub alias: String,
+
+    /// Project the alias points to, matched like the argument of cd
+    pub project_name: String,
+
+    /// Replace an alias that points to another project
+    #[arg(short, long = "force")]
+    pub force: bool,
+
+    #[command(flatten)]
+    pub detector: DetectorArgs,
+}
+
+#[derive(Args)]
+#[command(author, version, about="Remove an alias added with 'alias add'", long_about=None)]
+pub struct AliasRemoveArgs {
+    /// Name of the alias
+    pub alias: String,
+
+    #[command(flatten)]
+    pub detector: DetectorArgs,
+}
+
+#[derive(Args)]
+#[command(author, version, about="List all aliases and the projects they point to", long_about=None)]
+pub struct AliasListArgs {
+    #[command(flatten)]
+    pub detector: DetectorArgs,
+}
+
+#[derive(Args)]
+#[command(author, version, about="Rebuild the cached project index", long_about=None)]
+pub struct ReindexArgs {
+    #[command(flatten)]
+    pub detector: DetectorArgs,
+}
+
+#[derive(Args)]
+#[command(author, version, about="List all available projects", long_about=None)]
+pub struct ListProjectsArgs {
+    #[command(flatten)]
+    pub detector: DetectorArgs,
+
+    /// Output format
+    #[arg(short, long = "format", default_value = "plain")]
+    pub format: crate::output::format::Format,
+
+    /// Only list projects of this kind, may be repeated to require several kinds
+    #[arg(short, long = "kind")]
+    pub kinds: Vec<String>,
+
+    /// Only list projects with this tag, may be repeated to require several tags
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+
+    /// Also print the aliases of the projects, one per line, plain format only
+    #[arg(long = "with-aliases")]
+    pub with_aliases: bool,
+
+    /// Also list projects that are symlinks to another listed project
+    #[arg(long = "include-links")]
+    pub include_links: bool,
+
+    /// Order of the listed projects [default: namespace tree order]
+    #[arg(short, long = "sort", value_name = "KEY")]
+    pub sort: Option<crate::sort::SortKey>,
+
+    /// Print each project using a template like '{slug}\t{path}\t{kind}',
+    /// available placeholders: slug, name, path, kind, kinds, namespace,
+    /// description, version, tags, aliases
+    #[arg(short, long = "template", conflicts_with = "format")]
+    pub template: Option<crate::output::format::Template>,
+}
+
+#[derive(Args)]
+#[command(author, version, about="Display the namespace hierarchy", long_about=None)]
+pub struct TreeArgs {
+    /// Namespace to start from [default: all projects]
+    pub namespace: Option<String>,
+
+    #[command(flatten)]
+    pub detector: DetectorArgs,
+
+    /// Number of namespace levels to expand
+    #[arg(short, long = "depth")]
+    pub depth: Option<usize>,
+
+    /// Show Nerd Font icons for namespaces and project kinds
+    #[arg(long = "icons")]
+    pub icons: bool,
+
+    /// When to colorize the output
+    #[arg(long = "color", default_value = "auto")]
+    pub color: crate::output::tree::ColorChoice,
 }
 
 #[derive(Args)]
@@ -44,22 +259,36 @@ pub struct NewArgs {
 
     /// Directory where projects are stored
     #[arg(short, long = "projects-root")]
-    pub project_dir_path: std::path::PathBuf,
+    pub project_dir_path: Option<std::path::PathBuf>,
 
-    /// Generator used for creating new project
-    #[arg(short, long = "generator", default_value = "git")]
-    pub generator: String,
+    /// Configured root to create the project in [default: first root]
+    #[arg(short, long = "root", value_name = "PREFIX")]
+    pub root: Option<String>,
+
+    /// Generator used for creating new project [default: git]
+    #[arg(short, long = "generator")]
+    pub generator: Option<String>,
 }
 
 #[derive(Args)]
 #[command(author, version, about = "Change directory to specified project root", long_about = None)]
 pub struct CdArgs {
-    /// Project to switch to
+    /// Project to switch to, either its full slug, a unique suffix of it
+    /// or a fuzzy abbreviation, optionally followed by ':BOOKMARK' or
+    /// '/SUB/DIR'
     pub project_name: String,
 
-    /// Directory where projects are stored
-    #[arg(short, long = "projects-root")]
-    pub project_dir_path: std::path::PathBuf,
+    /// Print all matching projects, best match first, instead of a path
+    #[arg(short, long = "list")]
+    pub list: bool,
+
+    /// Print the completions of a partial PROJECT_NAME: slugs, aliases,
+    /// bookmarks and the subdirectories after a '/'
+    #[arg(long = "complete", conflicts_with = "list")]
+    pub complete: bool,
+
+    #[command(flatten)]
+    pub detector: DetectorArgs,
 }
 
 #[derive(Args)]
@@ -70,7 +299,11 @@ pub struct CloneArgs {
 
     /// Directory where projects are stored
     #[arg(short, long = "projects-root")]
-    pub project_dir_path: std::path::PathBuf,
+    pub project_dir_path: Option<std::path::PathBuf>,
+
+    /// Configured root to clone the project into [default: first root]
+    #[arg(short, long = "root", value_name = "PREFIX")]
+    pub root: Option<String>,
 
     /// Parent directory to clone project into
     #[arg(short, long = "project-name")]
@@ -1,49 +1,630 @@
 use anyhow::{Context, Result};
-use std::{fs, path::PathBuf, process};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::Arc,
+};
 
 use crate::cli;
+use crate::data::{
+    aliases::AliasTable,
+    cache,
+    config::{self, Config},
+    history::{self, History},
+};
+use crate::error::Error;
+use crate::matcher;
+use crate::output::{format, info, tree};
 use crate::project;
+use crate::sort;
+
+const DEFAULT_PROMPT_TEMPLATE: &str = "{slug} ({kinds})";
 
 fn parse_project_dir_path(project_dir_path: PathBuf) -> Result<PathBuf> {
-    Ok(project_dir_path.canonicalize().with_context(|| {
+    project_dir_path.canonicalize().with_context(|| {
         format!(
             "Cannot canonicalize project directory path: {}",
             project_dir_path.display()
         )
-    })?)
+    })
+}
+
+/// Pick the root new projects are placed in, either given on the command
+/// line or selected by `prefix` from the configured roots.
+fn projects_root(
+    project_dir_path: Option<PathBuf>,
+    prefix: Option<String>,
+    config: &Config,
+) -> Result<PathBuf> {
+    if let Some(path) = project_dir_path {
+        return Ok(path);
+    }
+
+    let roots = config.project_roots();
+    let root = match prefix {
+        Some(prefix) => roots
+            .into_iter()
+            .find(|root| root.prefix() == Some(prefix.as_str()))
+            .ok_or(Error::UnknownRoot(prefix))?,
+        None => roots.into_iter().next().ok_or(Error::NoProjectsRoot)?,
+    };
+    Ok(root.path().to_path_buf())
+}
+
+/// Roots given on the command line replace the configured ones.
+///
+/// Prefixes have to be valid slugs, so the projects below them can be
+/// resolved. Relative roots are made absolute, so paths in the output and
+/// the index do not depend on the directory the command runs in.
+fn project_roots(
+    project_dir_path: Option<PathBuf>,
+    mounts: Vec<project::ProjectRoot>,
+    config: &Config,
+) -> Result<Vec<project::ProjectRoot>> {
+    let roots: Vec<_> = if project_dir_path.is_some() || !mounts.is_empty() {
+        project_dir_path
+            .map(project::ProjectRoot::new)
+            .into_iter()
+            .chain(mounts)
+            .collect()
+    } else {
+        config.project_roots()
+    };
+
+    if roots.is_empty() {
+        Err(Error::NoProjectsRoot)?;
+    }
+    for prefix in roots.iter().filter_map(project::ProjectRoot::prefix) {
+        config
+            .slugs
+            .validate_slug(prefix)
+            .map_err(|reason| Error::InvalidRootPrefix {
+                prefix: prefix.to_string(),
+                reason,
+            })?;
+    }
+    roots
+        .into_iter()
+        .map(|root| {
+            let path = root.path().to_path_buf();
+            root.absolute()
+                .with_context(|| format!("Cannot resolve projects root: {}", path.display()))
+        })
+        .collect()
+}
+
+fn build_detector(args: &cli::DetectorArgs, config: &Config) -> Result<project::Detector> {
+    let roots = project_roots(args.project_dir_path.clone(), args.mounts.clone(), config)?;
+
+    let mut detector_config = project::DetectorConfig::default();
+    if let Some(ignore_hidden) = args.ignore_hidden.or(config.ignore_hidden) {
+        detector_config = detector_config.ignore_hidden_files(ignore_hidden);
+    }
+    if let Some(symlinks) = args.symlinks.or(config.symlinks) {
+        detector_config = detector_config.symlinks(symlinks);
+    }
+    detector_config = detector_config.max_depth(args.max_depth.or(config.max_depth));
+    if let Some(sub_projects) = args.sub_projects.or(config.sub_projects) {
+        detector_config = detector_config.sub_projects(sub_projects);
+    }
+    if let Some(submodules) = args.submodules.or(config.submodules) {
+        detector_config = detector_config.submodules(submodules);
+    }
+    detector_config = detector_config.slug_rules(config.slugs.clone())?;
+    for (slug, depth) in config.namespace_depths.iter() {
+        detector_config = detector_config.namespace_depth(slug.clone(), *depth);
+    }
+    for pattern in config
+        .ignore_patterns
+        .iter()
+        .chain(args.ignore_patterns.iter())
+    {
+        detector_config = detector_config.ignore_pattern(pattern.clone())?;
+    }
+    for name in config.disable_detectors.iter() {
+        detector_config = detector_config.disable_detector(name);
+    }
+    for definition in config.detectors.iter() {
+        detector_config = detector_config.detector(definition.build()?, definition.priority);
+    }
+
+    Ok(project::Detector::with_roots(roots, detector_config))
+}
+
+/// The projects roots and their canonical paths, for [`matcher::enclosing`].
+fn canonical_roots(args: &cli::DetectorArgs, config: &Config) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let roots = project_roots(args.project_dir_path.clone(), args.mounts.clone(), config)?;
+    Ok(roots
+        .iter()
+        .map(|root| {
+            let path = root.path().to_path_buf();
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+            (path, canonical)
+        })
+        .collect())
+}
+
+/// The canonical path of `path`, the current directory by default.
+fn canonical_current_dir(path: Option<PathBuf>) -> Result<PathBuf> {
+    let path = match path {
+        Some(path) => path,
+        None => std::env::current_dir().context("Cannot determine current directory")?,
+    };
+    path.canonicalize()
+        .with_context(|| format!("Cannot canonicalize path: {}", path.display()))
+}
+
+/// Detect all projects, reusing the cached index if it is still valid.
+fn detect(args: &cli::DetectorArgs, config: &Config) -> Result<project::RootNamespace> {
+    let detector = build_detector(args, config)?;
+    let root = detect_cached(detector, args.refresh)?;
+    report_warnings(&root, args.verbose);
+    Ok(root)
+}
+
+/// Detect with the cached index of `detector`, failing to update the
+/// index is only a warning.
+fn detect_cached(detector: project::Detector, refresh: bool) -> Result<project::RootNamespace> {
+    let detection = cache::detect(detector, &cache::try_init_cache_dir()?, refresh)?;
+    if let Some(err) = detection.store_error {
+        eprintln!("Warning: {:#}", err);
+    }
+    Ok(detection.root)
+}
+
+/// Print the slug collisions, and with `verbose` the problems found while
+/// scanning. `check` lists the collisions explicitly.
+fn report_warnings(root: &project::RootNamespace, verbose: bool) {
+    for collision in root.collisions() {
+        eprintln!("Warning: {}", collision);
+    }
+    if verbose {
+        for warning in root.warnings() {
+            eprintln!("Warning: {}", warning);
+        }
+        for slug in root.truncated_namespaces() {
+            eprintln!("Warning: '{}' was truncated at the maximum depth", slug);
+        }
+    }
+}
+
+/// Aliases of the config file and the alias table, the table takes
+/// precedence.
+fn configured_aliases(config: &Config) -> Result<BTreeMap<String, String>> {
+    let table = AliasTable::load(&config::try_init_config_dir()?)?;
+    let mut aliases = config.aliases.clone();
+    aliases.extend(
+        table
+            .iter()
+            .map(|(alias, slug)| (alias.to_string(), slug.to_string())),
+    );
+    Ok(aliases)
+}
+
+/// Slugs of all projects, with the configured aliases added to the
+/// projects they point to.
+///
+/// Aliases that are the slug of a project are ignored and aliases of
+/// unknown projects are dropped, both are reported if `verbose`.
+fn project_slugs(
+    root: &project::RootNamespace,
+    config: &Config,
+    verbose: bool,
+) -> Result<Vec<project::SlugPath>> {
+    let mut slug_paths = root.build_project_slugs();
+    let indices: HashMap<Arc<str>, usize> = slug_paths
+        .iter()
+        .enumerate()
+        .map(|(index, slug_path)| (Arc::from(slug_path.slug()), index))
+        .collect();
+
+    for (alias, slug) in configured_aliases(config)? {
+        if indices.contains_key(alias.as_str()) {
+            if verbose {
+                eprintln!(
+                    "Warning: alias '{}' is the slug of a project and is ignored",
+                    alias
+                );
+            }
+            continue;
+        }
+        match indices.get(slug.as_str()) {
+            Some(&index) => slug_paths[index].add_alias(alias.into()),
+            None if verbose => eprintln!(
+                "Warning: alias '{}' points to unknown project '{}'",
+                alias, slug
+            ),
+            None => {}
+        }
+    }
+
+    Ok(slug_paths)
+}
+
+pub fn cd(args: cli::CdArgs, config: &Config) -> Result<()> {
+    let root = detect(&args.detector, config)?;
+
+    let slug_paths = project_slugs(&root, config, args.detector.verbose)?;
+
+    if args.list {
+        for m in matcher::rank(&args.project_name, &slug_paths) {
+            println!("{}", m.slug_path());
+        }
+        return Ok(());
+    }
+
+    if args.complete {
+        return complete_cd(&args.project_name, &slug_paths);
+    }
+
+    let (slug_path, subpath) = matcher::resolve_target(&args.project_name, &slug_paths)?;
+    let path = if subpath.as_os_str().is_empty() {
+        slug_path.path().to_path_buf()
+    } else {
+        slug_path.path().join(subpath)
+    };
+    if !path.is_dir() {
+        Err(Error::DirectoryNotFound(path.clone()))?;
+    }
+    println!("{}", path.display());
+
+    // The path is printed already, failing to record the visit must not
+    // make the shell wrapper treat the switch as failed.
+    if let Err(err) = record_visit(slug_path.path()) {
+        eprintln!("Warning: {:#}", err);
+    }
+
+    Ok(())
+}
+
+fn record_visit(path: &std::path::Path) -> Result<()> {
+    let data_dir = history::try_init_data_dir()?;
+    let mut history = History::load(&data_dir);
+    history.visit(path);
+    history.store(&data_dir)
+}
+
+/// Print the completions of a partial argument of `cd`.
+///
+/// Slugs, aliases and `slug:bookmark` are always printed, the shell picks
+/// the ones matching. Once `query` contains a `/`, the subdirectories of
+/// the directory before the last `/` follow as `query/dir/`.
+fn complete_cd(query: &str, slug_paths: &[project::SlugPath]) -> Result<()> {
+    let mut out = io::stdout().lock();
+    for slug_path in slug_paths.iter().filter(|slug_path| !slug_path.is_link()) {
+        writeln!(out, "{}", slug_path.slug())?;
+        for alias in slug_path.aliases() {
+            writeln!(out, "{}", alias)?;
+        }
+        for bookmark in slug_path.metadata().bookmarks().keys() {
+            writeln!(
+                out,
+                "{}{}{}",
+                slug_path.slug(),
+                project::MEMBER_SEPARATOR,
+                bookmark
+            )?;
+        }
+    }
+
+    let Some((dir_query, partial)) = query.rsplit_once(project::PATH_SEPARATOR) else {
+        return Ok(());
+    };
+    let Ok((slug_path, subpath)) = matcher::resolve_target(dir_query, slug_paths) else {
+        return Ok(());
+    };
+    let Ok(entries) = fs::read_dir(slug_path.path().join(subpath)) else {
+        return Ok(());
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with(partial))
+        .filter(|name| partial.starts_with('.') || !name.starts_with('.'))
+        .collect();
+    names.sort();
+    for name in names {
+        writeln!(out, "{}/{}/", dir_query, name)?;
+    }
+    Ok(())
 }
 
-pub fn cd(args: cli::CdArgs) -> Result<()> {
-    let root = project::Detector::new(args.project_dir_path).detect();
+pub fn list_projects(args: cli::ListProjectsArgs, config: &Config) -> Result<()> {
+    let root = detect(&args.detector, config)?;
+
+    let kinds: Vec<project::ProjectKind> = args.kinds.iter().map(|k| k.as_str().into()).collect();
+    let mut slug_paths: Vec<_> = project_slugs(&root, config, args.detector.verbose)?
+        .into_iter()
+        .filter(|slug_path| args.include_links || !slug_path.is_link())
+        .filter(|slug_path| kinds.iter().all(|kind| slug_path.kinds().contains(kind)))
+        .filter(|slug_path| {
+            args.tags
+                .iter()
+                .all(|tag| slug_path.metadata().has_tag(tag))
+        })
+        .collect();
 
-    let slug_paths = root.build_project_slugs();
+    if let Some(key) = args.sort {
+        let history = match key {
+            sort::SortKey::Frecency => History::load(&history::try_init_data_dir()?),
+            _ => History::default(),
+        };
+        sort::sort(&mut slug_paths, key, &history);
+    }
 
-    for slug_path in slug_paths {
-        if slug_path.to_string() == args.project_name {
-            println!("{}", slug_path.fmt_path());
+    let mut out = io::stdout().lock();
+    match &args.template {
+        Some(template) => format::write_template(&mut out, &slug_paths, template)?,
+        None => format::write_projects(&mut out, &slug_paths, args.format)?,
+    }
+    if args.with_aliases && args.template.is_none() && args.format == format::Format::Plain {
+        for alias in slug_paths.iter().flat_map(|slug_path| slug_path.aliases()) {
+            writeln!(out, "{}", alias)?;
         }
     }
 
     Ok(())
 }
 
-pub fn list_projects(args: cli::ListProjectsArgs) -> Result<()> {
-    let root = project::Detector::new(args.project_dir_path).detect();
+pub fn tree(args: cli::TreeArgs, config: &Config) -> Result<()> {
+    let root = detect(&args.detector, config)?;
+    let style = tree::TreeStyle {
+        depth: args.depth,
+        icons: args.icons,
+        color: args.color.enabled(),
+    };
 
-    let slug_paths = root.build_project_slugs();
+    let mut out = io::stdout().lock();
+    match &args.namespace {
+        Some(slug) => {
+            let namespace = root
+                .find_namespace(slug)
+                .ok_or_else(|| Error::NamespaceNotFound(slug.clone()))?;
+            tree::write_tree(
+                &mut out,
+                slug,
+                namespace.project_count(),
+                namespace.items(),
+                &style,
+            )?;
+        }
+        None => {
+            tree::write_tree(&mut out, ".", root.project_count(), root.items(), &style)?;
+        }
+    }
 
-    for slug_path in slug_paths {
-        println!("{}", slug_path);
+    Ok(())
+}
+
+pub fn info(args: cli::InfoArgs, config: &Config) -> Result<()> {
+    let root = detect(&args.detector, config)?;
+    let slug_paths = project_slugs(&root, config, args.detector.verbose)?;
+    let slug_path = matcher::resolve(&args.project_name, &slug_paths)?;
+
+    let mut out = io::stdout().lock();
+    if args.json {
+        serde_json::to_writer_pretty(&mut out, slug_path)?;
+        writeln!(out)?;
+    } else {
+        info::write_info(&mut out, slug_path)?;
     }
 
     Ok(())
 }
 
-pub fn new(args: cli::NewArgs) -> Result<()> {
-    let project_dir = parse_project_dir_path(args.project_dir_path)?.join(&args.project_name);
+/// Print the project containing a path, the current directory by default.
+pub fn where_(args: cli::WhereArgs, config: &Config) -> Result<()> {
+    let path = canonical_current_dir(args.path)?;
+
+    let root = detect(&args.detector, config)?;
+    let (slug_path, subpath) = enclosing_project(&path, &root, &args.detector, config)?
+        .ok_or(Error::NotInProject(path.clone()))?;
+    let location = info::Location::new(&slug_path, subpath);
+
+    let mut out = io::stdout().lock();
+    if args.json {
+        serde_json::to_writer_pretty(&mut out, &location)?;
+        writeln!(out)?;
+    } else {
+        info::write_location(&mut out, &location, slug_path.separator())?;
+    }
+
+    Ok(())
+}
+
+/// The project containing the canonical `path` and `path` relative to it.
+///
+/// `where` and `prompt` both look up projects this way, so they agree with
+/// each other and with `cd` on the slug of a directory.
+fn enclosing_project(
+    path: &Path,
+    root: &project::RootNamespace,
+    args: &cli::DetectorArgs,
+    config: &Config,
+) -> Result<Option<(project::SlugPath, PathBuf)>> {
+    let slug_paths = project_slugs(root, config, args.verbose)?;
+    let roots = canonical_roots(args, config)?;
+    Ok(matcher::enclosing(path, &slug_paths, &roots)
+        .map(|(slug_path, subpath)| (slug_path.clone(), subpath)))
+}
+
+/// Print a prompt segment for the project containing the current
+/// directory, nothing outside of projects.
+///
+/// Prompts run far too often for a scan, the project is looked up in the
+/// index even if it is out of date. Without an index nothing is printed,
+/// the index is only built by the other commands.
+pub fn prompt(args: cli::PromptArgs, config: &Config) -> Result<()> {
+    let template = match (args.template, &config.prompt_template) {
+        (Some(template), _) => template,
+        (None, Some(template)) => template.parse()?,
+        (None, None) => DEFAULT_PROMPT_TEMPLATE.parse()?,
+    };
+    // The prompt is also drawn in directories that were deleted meanwhile.
+    let Ok(path) = canonical_current_dir(None) else {
+        return Ok(());
+    };
+
+    let detector = build_detector(&args.detector, config)?;
+    let Some(root) = cache::load_stale(&detector, &cache::try_init_cache_dir()?) else {
+        return Ok(());
+    };
+    let Some((slug_path, _)) = enclosing_project(&path, &root, &args.detector, config)? else {
+        return Ok(());
+    };
+
+    let mut out = io::stdout().lock();
+    write!(out, "{}", template.render(&slug_path))?;
+    if !args.starship {
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+pub fn alias(args: cli::AliasArgs, config: &Config) -> Result<()> {
+    match args.command {
+        cli::AliasCommands::Add(args) => alias_add(args, config),
+        cli::AliasCommands::Remove(args) => alias_remove(args, config),
+        cli::AliasCommands::List(args) => alias_list(args, config),
+    }
+}
+
+fn alias_add(args: cli::AliasAddArgs, config: &Config) -> Result<()> {
+    let invalid = |reason: String| Error::InvalidAlias {
+        alias: args.alias.clone(),
+        reason,
+    };
+    config.slugs.validate_slug(&args.alias).map_err(invalid)?;
+    if config.aliases.contains_key(&args.alias) {
+        Err(invalid("it is defined in the config file".to_string()))?;
+    }
+
+    let root = detect(&args.detector, config)?;
+    let slug_paths = project_slugs(&root, config, args.detector.verbose)?;
+    if slug_paths
+        .iter()
+        .any(|slug_path| slug_path.slug() == args.alias)
+    {
+        Err(invalid("it is the slug of a project".to_string()))?;
+    }
+    if let Some(owner) = slug_paths
+        .iter()
+        .find(|slug_path| slug_path.metadata().aliases().contains(&args.alias))
+    {
+        Err(invalid(format!(
+            "it is defined in the project file of '{}'",
+            owner.slug()
+        )))?;
+    }
+    let slug = matcher::resolve(&args.project_name, &slug_paths)?.slug();
+
+    let config_dir = config::try_init_config_dir()?;
+    let mut table = AliasTable::load(&config_dir)?;
+    match table.get(&args.alias) {
+        Some(previous) if previous != slug && !args.force => Err(invalid(format!(
+            "it points to '{}', pass --force to replace it",
+            previous
+        )))?,
+        _ => {}
+    }
+    match table.insert(args.alias.clone(), slug.to_string()) {
+        Some(previous) if previous != slug => eprintln!(
+            "Changed alias '{}' from '{}' to '{}'",
+            args.alias, previous, slug
+        ),
+        _ => eprintln!("Added alias '{}' for '{}'", args.alias, slug),
+    }
+    table.store(&config_dir)?;
+    Ok(())
+}
+
+fn alias_remove(args: cli::AliasRemoveArgs, config: &Config) -> Result<()> {
+    let config_dir = config::try_init_config_dir()?;
+    let mut table = AliasTable::load(&config_dir)?;
+    match table.remove(&args.alias) {
+        Some(slug) => {
+            table.store(&config_dir)?;
+            eprintln!("Removed alias '{}' for '{}'", args.alias, slug);
+            Ok(())
+        }
+        None if config.aliases.contains_key(&args.alias) => Err(Error::InvalidAlias {
+            alias: args.alias,
+            reason: "it is defined in the config file".to_string(),
+        })?,
+        None => Err(Error::AliasNotFound(args.alias))?,
+    }
+}
+
+/// Print `alias<TAB>slug` for the aliases of all projects, from project
+/// files and the configuration.
+fn alias_list(args: cli::AliasListArgs, config: &Config) -> Result<()> {
+    let root = detect(&args.detector, config)?;
+    let slug_paths = project_slugs(&root, config, args.detector.verbose)?;
+
+    let mut aliases: Vec<(&str, &str)> = slug_paths
+        .iter()
+        .flat_map(|slug_path| {
+            slug_path
+                .aliases()
+                .iter()
+                .map(|alias| (alias.as_ref(), slug_path.slug()))
+        })
+        .collect();
+    aliases.sort();
+
+    let mut out = io::stdout().lock();
+    for (alias, slug) in aliases {
+        writeln!(out, "{}\t{}", alias, slug)?;
+    }
+    Ok(())
+}
+
+pub fn reindex(args: cli::ReindexArgs, config: &Config) -> Result<()> {
+    let detector = build_detector(&args.detector, config)?;
+    let root = detect_cached(detector, true)?;
+    report_warnings(&root, args.detector.verbose);
+    eprintln!("Indexed {} projects", root.project_count());
+    Ok(())
+}
+
+/// Print all slug collisions, failing if there are any.
+pub fn check(args: cli::CheckArgs, config: &Config) -> Result<()> {
+    let detector = build_detector(&args.detector, config)?;
+    let root = detect_cached(detector, args.detector.refresh)?;
+    if args.detector.verbose {
+        for warning in root.warnings() {
+            eprintln!("Warning: {}", warning);
+        }
+    }
+
+    let collisions = root.collisions();
+    if collisions.is_empty() {
+        eprintln!("No slug collisions in {} projects", root.project_count());
+        return Ok(());
+    }
+
+    let mut out = io::stdout().lock();
+    for collision in collisions {
+        writeln!(out, "{}", collision)?;
+    }
+    Err(Error::SlugCollisions(collisions.len()))?
+}
+
+pub fn new(args: cli::NewArgs, config: &Config) -> Result<()> {
+    let generator = args
+        .generator
+        .or_else(|| config.default_generator.clone())
+        .unwrap_or_else(|| "git".to_string());
+    let project_dir =
+        parse_project_dir_path(projects_root(args.project_dir_path, args.root, config)?)?
+            .join(&args.project_name);
     fs::create_dir(&project_dir)
         .with_context(|| format!("Cannot create project '{}'", &args.project_name))?;
-    if args.generator == "git" {
+    if generator == "git" {
         process::Command::new("git")
             .arg("init")
             .arg(project_dir)
@@ -52,12 +633,13 @@ pub fn new(args: cli::NewArgs) -> Result<()> {
     } else {
         Ok(())
     }
-    .with_context(|| format!("Generator {} could not be executed", args.generator))?;
+    .with_context(|| format!("Generator {} could not be executed", generator))?;
     Ok(())
 }
 
-pub fn clone(args: cli::CloneArgs) -> Result<()> {
-    let project_dir_path = parse_project_dir_path(args.project_dir_path)?;
+pub fn clone(args: cli::CloneArgs, config: &Config) -> Result<()> {
+    let project_dir_path =
+        parse_project_dir_path(projects_root(args.project_dir_path, args.root, config)?)?;
 
     // TODO: Maybe do some checks on the specified URL before passing it
     // to git
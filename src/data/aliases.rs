@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+const ALIASES_FILE_NAME: &str = "aliases.yaml";
+
+/// Aliases managed with `alias add` and `alias remove`, stored in
+/// `<config_dir>/aliases.yaml` as a mapping from alias to slug.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AliasTable {
+    aliases: BTreeMap<String, String>,
+}
+
+impl AliasTable {
+    /// Load the alias table from `config_dir`, a missing table is empty.
+    pub fn load(config_dir: &Path) -> Result<Self> {
+        let aliases_path = config_dir.join(ALIASES_FILE_NAME);
+        let content = match fs::read_to_string(&aliases_path) {
+            Ok(content) => content,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("Cannot read aliases '{}'", aliases_path.display()))
+            }
+        };
+        if content.trim().is_empty() {
+            return Ok(Self::default());
+        }
+
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Invalid aliases '{}'", aliases_path.display()))
+    }
+
+    pub fn store(&self, config_dir: &Path) -> Result<()> {
+        let aliases_path = config_dir.join(ALIASES_FILE_NAME);
+        let tmp_path = aliases_path.with_extension(format!("yaml.{}", std::process::id()));
+        fs::write(&tmp_path, serde_yaml::to_string(self)?)
+            .and_then(|_| fs::rename(&tmp_path, &aliases_path))
+            .with_context(|| format!("Cannot write aliases '{}'", aliases_path.display()))
+    }
+
+    /// Point `alias` to the project `slug`, returning the slug it pointed
+    /// to before.
+    pub fn insert(&mut self, alias: String, slug: String) -> Option<String> {
+        self.aliases.insert(alias, slug)
+    }
+
+    pub fn remove(&mut self, alias: &str) -> Option<String> {
+        self.aliases.remove(alias)
+    }
+
+    pub fn get(&self, alias: &str) -> Option<&str> {
+        self.aliases.get(alias).map(String::as_str)
+    }
+
+    /// Aliases and the slugs they point to, sorted by alias.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases
+            .iter()
+            .map(|(alias, slug)| (alias.as_str(), slug.as_str()))
+    }
+}
@@ -0,0 +1,272 @@
+use crate::error::Error;
+use crate::project::{Detector, RootNamespace};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+const CACHE_NAME: &str = "project-manager";
+
+/// Layout of the index file, to be increased whenever the serialized form
+/// of the index or the projects in it changes.
+const INDEX_VERSION: u32 = 1;
+
+/// Detection result stored on disk, together with the modification times
+/// of every directory and ignore file it was built from.
+#[derive(Serialize, Deserialize)]
+struct Index {
+    version: u32,
+    fingerprint: String,
+    stamps: Vec<(PathBuf, SystemTime)>,
+    root: RootNamespace,
+}
+
+impl Index {
+    fn is_compatible(&self, fingerprint: &str) -> bool {
+        self.version == INDEX_VERSION && self.fingerprint == fingerprint
+    }
+
+    fn is_fresh(&self, fingerprint: &str) -> bool {
+        self.is_compatible(fingerprint)
+            && self
+                .stamps
+                .iter()
+                .all(|(path, stamp)| modified(path).is_some_and(|modified| modified == *stamp))
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+pub fn try_init_cache_dir() -> Result<PathBuf> {
+    // Priority which directory should be used for the cache
+    // 1. $XDG_CACHE_HOME/<CACHE_NAME>
+    // 2. $HOME/.cache/<CACHE_NAME>
+
+    let cache_location = super::xdg_dir("XDG_CACHE_HOME", ".cache", CACHE_NAME).ok_or(
+        Error::CouldNotDetermineCacheLocation(vec![
+            format!("$XDG_CACHE_HOME/{}", CACHE_NAME),
+            format!("$HOME/.cache/{}", CACHE_NAME),
+        ]),
+    )?;
+
+    fs::create_dir_all(&cache_location).with_context(|| {
+        format!(
+            "Failed to create cache directory at '{}'",
+            cache_location.display()
+        )
+    })?;
+
+    Ok(cache_location)
+}
+
+/// Every detector configuration gets its own index file, so switching
+/// between roots does not invalidate the other indexes.
+fn index_path(cache_dir: &Path, fingerprint: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    fingerprint.hash(&mut hasher);
+    cache_dir.join(format!("index-{:016x}.json", hasher.finish()))
+}
+
+/// Projects found by [`detect`].
+pub struct Detection {
+    pub root: RootNamespace,
+    /// Why the updated index could not be written, the projects were
+    /// detected nonetheless
+    pub store_error: Option<anyhow::Error>,
+}
+
+/// Return the cached detection result of `detector` if none of the scanned
+/// paths changed since it was stored, otherwise detect again and
+/// update the index.
+///
+/// `refresh` skips the cached result unconditionally.
+pub fn detect(detector: Detector, cache_dir: &Path, refresh: bool) -> Result<Detection> {
+    let fingerprint = detector.fingerprint();
+    let index_path = index_path(cache_dir, &fingerprint);
+
+    if !refresh {
+        if let Some(index) = load(&index_path).filter(|index| index.is_fresh(&fingerprint)) {
+            return Ok(Detection {
+                root: index.root,
+                store_error: None,
+            });
+        }
+    }
+
+    let root = detector.detect()?;
+    let stamps = root
+        .scanned_paths()
+        .iter()
+        .filter_map(|path| modified(path).map(|stamp| (path.clone(), stamp)))
+        .collect();
+    let index = Index {
+        version: INDEX_VERSION,
+        fingerprint,
+        stamps,
+        root,
+    };
+
+    let store_error = store(&index_path, &index).err();
+    Ok(Detection {
+        root: index.root,
+        store_error,
+    })
+}
+
+/// The stored detection result of `detector`, even if some of the scanned
+/// paths changed since. Nothing is detected if there is no index.
+///
+/// Checking the index is as expensive as listing every namespace
+/// directory, this is meant for lookups like prompts that run far too
+/// often to scan and can live with an index that is slightly out of date.
+pub fn load_stale(detector: &Detector, cache_dir: &Path) -> Option<RootNamespace> {
+    let fingerprint = detector.fingerprint();
+    load(&index_path(cache_dir, &fingerprint))
+        .filter(|index| index.is_compatible(&fingerprint))
+        .map(|index| index.root)
+}
+
+fn load(index_path: &Path) -> Option<Index> {
+    fs::read(index_path)
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+}
+
+fn store(index_path: &Path, index: &Index) -> Result<()> {
+    // Write to a temporary file first, so concurrent readers never see a
+    // partially written index.
+    let tmp_path = index_path.with_extension(format!("json.{}", std::process::id()));
+    fs::write(&tmp_path, serde_json::to_vec(index)?)
+        .and_then(|_| fs::rename(&tmp_path, index_path))
+        .with_context(|| format!("Cannot write project index '{}'", index_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::DetectorConfig;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn detect_kinds(root: &Path, cache_dir: &Path, refresh: bool) -> Vec<String> {
+        let detector = Detector::with_config(root.to_path_buf(), DetectorConfig::default());
+        let detection = detect(detector, cache_dir, refresh).unwrap();
+        assert!(detection.store_error.is_none());
+        detection
+            .root
+            .build_project_slugs()
+            .iter()
+            .map(|slug_path| format!("{} {}", slug_path.slug(), slug_path.kinds()))
+            .collect()
+    }
+
+    fn add_project(dir: &Path, marker: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(marker), "").unwrap();
+    }
+
+    /// Give `dirs` the same modification time, some time in the past.
+    fn set_modified(dirs: &[&Path]) {
+        for dir in dirs {
+            fs::File::open(dir)
+                .unwrap()
+                .set_modified(UNIX_EPOCH + Duration::from_secs(1_000_000_000))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn reuses_fresh_index() {
+        let root = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        add_project(&root.path().join("a"), "Makefile");
+        set_modified(&[root.path()]);
+        assert_eq!(
+            detect_kinds(root.path(), cache_dir.path(), false),
+            ["a make"]
+        );
+
+        // Unnoticed, the modification time of the root is unchanged
+        add_project(&root.path().join("b"), "Makefile");
+        set_modified(&[root.path()]);
+        assert_eq!(
+            detect_kinds(root.path(), cache_dir.path(), false),
+            ["a make"]
+        );
+        assert_eq!(
+            detect_kinds(root.path(), cache_dir.path(), true),
+            ["a make", "b make"]
+        );
+    }
+
+    #[test]
+    fn rebuilds_stale_index() {
+        let root = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        add_project(&root.path().join("a"), "Makefile");
+        set_modified(&[root.path(), &root.path().join("a")]);
+        assert_eq!(
+            detect_kinds(root.path(), cache_dir.path(), false),
+            ["a make"]
+        );
+
+        add_project(&root.path().join("b"), "Makefile");
+        assert_eq!(
+            detect_kinds(root.path(), cache_dir.path(), false),
+            ["a make", "b make"]
+        );
+
+        fs::remove_file(root.path().join("a/Makefile")).unwrap();
+        fs::write(root.path().join("a/build.zig"), "").unwrap();
+        assert_eq!(
+            detect_kinds(root.path(), cache_dir.path(), false),
+            ["a zig", "b make"]
+        );
+    }
+
+    #[test]
+    fn index_is_keyed_by_root() {
+        let roots = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let (a, b) = (roots.path().join("a"), roots.path().join("b"));
+        add_project(&a.join("x"), "Makefile");
+        add_project(&b.join("x"), "build.zig");
+        set_modified(&[&a, &b, &a.join("x"), &b.join("x")]);
+
+        assert_eq!(detect_kinds(&a, cache_dir.path(), false), ["x make"]);
+        assert_eq!(detect_kinds(&b, cache_dir.path(), false), ["x zig"]);
+        assert_eq!(detect_kinds(&a, cache_dir.path(), false), ["x make"]);
+    }
+
+    #[test]
+    fn ignores_index_of_other_version() {
+        let root = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        add_project(&root.path().join("a"), "Makefile");
+        detect_kinds(root.path(), cache_dir.path(), false);
+
+        let detector = Detector::with_config(root.path().to_path_buf(), DetectorConfig::default());
+        let index_path = index_path(cache_dir.path(), &detector.fingerprint());
+        let rewrite_index = |version: u32| {
+            let mut index: serde_json::Value =
+                serde_json::from_slice(&fs::read(&index_path).unwrap()).unwrap();
+            index["version"] = version.into();
+            index["root"]["items"] = serde_json::json!([]);
+            fs::write(&index_path, serde_json::to_vec(&index).unwrap()).unwrap();
+        };
+
+        rewrite_index(INDEX_VERSION + 1);
+        assert_eq!(
+            detect_kinds(root.path(), cache_dir.path(), false),
+            ["a make"]
+        );
+        rewrite_index(INDEX_VERSION);
+        assert!(detect_kinds(root.path(), cache_dir.path(), false).is_empty());
+    }
+}
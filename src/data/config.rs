@@ -1,10 +1,169 @@
 use crate::error::Error;
+use crate::project::{MarkerDetector, ProjectRoot, SlugRules, SymlinkPolicy};
 use anyhow::{anyhow, Context, Result};
-use std::{ffi::OsString, fs, io, path::PathBuf};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 const CONFIG_NAME: &str = "project-manager";
+const CONFIG_FILE_NAME: &str = "config.yaml";
 
-pub fn try_init_config_dir() -> Result<()> {
+/// Settings read from `<config_dir>/config.yaml`.
+///
+/// Every field is optional, command line flags take precedence over the
+/// values configured here.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// Directory where projects are stored
+    pub projects_root: Option<PathBuf>,
+    /// Additional directories mounted below their own slug prefix
+    pub roots: Vec<RootConfig>,
+    /// Skip hidden files and directories while detecting projects
+    pub ignore_hidden: Option<bool>,
+    /// How symlinks to directories are treated while detecting projects
+    pub symlinks: Option<SymlinkPolicy>,
+    /// Only scan directories up to this many levels below a root
+    pub max_depth: Option<usize>,
+    /// Levels scanned below a namespace, by fully qualified slug,
+    /// overriding `max_depth`
+    pub namespace_depths: BTreeMap<String, usize>,
+    /// List workspace members as sub-projects
+    pub sub_projects: Option<bool>,
+    /// List checked out git submodules as sub-projects
+    pub submodules: Option<bool>,
+    /// How slugs are derived from directory names
+    pub slugs: SlugRules,
+    /// Short names for projects, mapped to their slugs
+    pub aliases: BTreeMap<String, String>,
+    /// Directory entries matching one of these patterns are skipped
+    pub ignore_patterns: Vec<String>,
+    /// Template of the segment printed by `prompt`
+    pub prompt_template: Option<String>,
+    /// Generator used by `new` when none is specified
+    pub default_generator: Option<String>,
+    /// Additional marker file detectors
+    pub detectors: Vec<DetectorDefinition>,
+    /// Names of detectors that are not run, e.g. built-in ones
+    pub disable_detectors: Vec<String>,
+}
+
+/// Detector recognising projects by marker files, e.g.
+///
+/// ```yaml
+/// detectors:
+///   - name: python
+///     markers: [pyproject.toml, setup.py]
+///     priority: 10
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DetectorDefinition {
+    pub name: String,
+    /// Kind of detected projects [default: name]
+    pub kind: Option<String>,
+    /// File names or glob patterns, one of them has to exist in a project
+    pub markers: Vec<String>,
+    /// String the matching marker file has to contain
+    pub contains: Option<String>,
+    /// Detectors with higher priority run first, built-in ones have 0
+    #[serde(default)]
+    pub priority: i32,
+}
+
+impl DetectorDefinition {
+    pub fn build(&self) -> Result<MarkerDetector, Error> {
+        let mut detector = MarkerDetector::from_globs(&self.name, &self.markers)?;
+        if let Some(kind) = &self.kind {
+            detector = detector.with_kind(kind.as_str().into());
+        }
+        if let Some(contents) = &self.contains {
+            detector = detector.with_contents(contents.clone());
+        }
+        Ok(detector)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RootConfig {
+    /// Slug prefix the projects of this root are listed under
+    pub prefix: String,
+    pub path: PathBuf,
+}
+
+impl Config {
+    /// Load the config file from `config_dir`, falling back to the default
+    /// config if the file does not exist.
+    pub fn load(config_dir: &Path) -> Result<Config> {
+        let config_file = config_dir.join(CONFIG_FILE_NAME);
+        let content = match fs::read_to_string(&config_file) {
+            Ok(content) => content,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!("Cannot read config file '{}'", config_file.display())
+                })
+            }
+        };
+
+        Config::parse(&content)
+            .with_context(|| format!("Invalid config file '{}'", config_file.display()))
+    }
+
+    pub fn parse(content: &str) -> Result<Config> {
+        // An empty file is not valid YAML mapping, but should behave like
+        // a missing one.
+        if content.trim().is_empty() {
+            return Ok(Config::default());
+        }
+
+        let mut config: Config = serde_yaml::from_str(content)?;
+        for alias in config.aliases.keys() {
+            config
+                .slugs
+                .validate_slug(alias)
+                .map_err(|reason| Error::InvalidAlias {
+                    alias: alias.clone(),
+                    reason,
+                })?;
+        }
+        config.projects_root = config.projects_root.map(expand_home);
+        for root in config.roots.iter_mut() {
+            root.path = expand_home(std::mem::take(&mut root.path));
+        }
+        Ok(config)
+    }
+
+    /// All configured project roots, `projects-root` first.
+    pub fn project_roots(&self) -> Vec<ProjectRoot> {
+        self.projects_root
+            .iter()
+            .map(|path| ProjectRoot::new(path.clone()))
+            .chain(
+                self.roots.iter().map(|root| {
+                    ProjectRoot::mounted(root.prefix.as_str().into(), root.path.clone())
+                }),
+            )
+            .collect()
+    }
+}
+
+/// Replace a leading `~` with the home directory of the current user.
+pub fn expand_home(path: PathBuf) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => match std::env::var_os("HOME") {
+            Some(home) if !home.is_empty() => PathBuf::from(home).join(rest),
+            _ => path,
+        },
+        Err(_) => path,
+    }
+}
+
+pub fn try_init_config_dir() -> Result<PathBuf> {
     // Priority which directory should be used for config
     // 1. $XDG_CONFIG_HOME/<CONFIG_NAME>
     // 2. $HOME/.config/<CONFIG_NAME>
@@ -36,37 +195,28 @@ pub fn try_init_config_dir() -> Result<()> {
     //          - git/
     //                  - 00-init.sh
 
-    fn empty_os_string_to_none(os_str: OsString) -> Option<PathBuf> {
-        if os_str.is_empty() {
-            None
-        } else {
-            Some(os_str.into())
-        }
-    }
-
-    let config_location: PathBuf = std::env::var_os("XDG_CONFIG_HOME")
-        .and_then(empty_os_string_to_none)
-        .or(std::env::var_os("HOME")
-            .and_then(empty_os_string_to_none)
-            .map(|path| path.join(".config")))
-        .map(|path| path.join(CONFIG_NAME))
-        .ok_or(Error::CouldNotDetermineConfigLocation(vec![
+    let config_location = super::xdg_dir("XDG_CONFIG_HOME", ".config", CONFIG_NAME).ok_or(
+        Error::CouldNotDetermineConfigLocation(vec![
             format!("$XDG_CONFIG_HOME/{}", CONFIG_NAME),
             format!("$HOME/.config/{}", CONFIG_NAME),
-        ]))?;
+        ]),
+    )?;
 
     match fs::metadata(&config_location) {
         Ok(_) => Ok(()),
         Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => {
             Err(anyhow!("No Permission for '{}'", config_location.display()))
         }
-        Err(ref err) if err.kind() == io::ErrorKind::NotFound => fs::create_dir(&config_location)
-            .with_context(|| {
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+            fs::create_dir_all(&config_location).with_context(|| {
                 format!(
                     "Failed to create config directory at '{}'",
                     config_location.display()
                 )
-            }),
+            })
+        }
         Err(_) => Err(anyhow!("")),
-    }
+    }?;
+
+    Ok(config_location)
 }
@@ -0,0 +1,100 @@
+use crate::error::Error;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const DATA_NAME: &str = "project-manager";
+const HISTORY_FILE_NAME: &str = "history.json";
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+pub fn try_init_data_dir() -> Result<PathBuf> {
+    // Priority which directory should be used for data
+    // 1. $XDG_DATA_HOME/<DATA_NAME>
+    // 2. $HOME/.local/share/<DATA_NAME>
+
+    let data_location = super::xdg_dir("XDG_DATA_HOME", ".local/share", DATA_NAME).ok_or(
+        Error::CouldNotDetermineDataLocation(vec![
+            format!("$XDG_DATA_HOME/{}", DATA_NAME),
+            format!("$HOME/.local/share/{}", DATA_NAME),
+        ]),
+    )?;
+
+    fs::create_dir_all(&data_location).with_context(|| {
+        format!(
+            "Failed to create data directory at '{}'",
+            data_location.display()
+        )
+    })?;
+
+    Ok(data_location)
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+struct Visits {
+    count: u64,
+    /// Seconds since the Unix epoch
+    last_visit: u64,
+}
+
+/// Projects switched to with `cd`, used to sort by frecency.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    visits: HashMap<PathBuf, Visits>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+impl History {
+    /// Load the history from `data_dir`, a missing or unreadable history
+    /// is empty.
+    pub fn load(data_dir: &Path) -> Self {
+        fs::read(data_dir.join(HISTORY_FILE_NAME))
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn store(&self, data_dir: &Path) -> Result<()> {
+        let history_path = data_dir.join(HISTORY_FILE_NAME);
+        let tmp_path = history_path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&tmp_path, serde_json::to_vec(self)?)
+            .and_then(|_| fs::rename(&tmp_path, &history_path))
+            .with_context(|| format!("Cannot write history '{}'", history_path.display()))
+    }
+
+    /// Record a visit of the project at `path`.
+    pub fn visit(&mut self, path: &Path) {
+        let visits = self.visits.entry(path.to_path_buf()).or_default();
+        visits.count += 1;
+        visits.last_visit = now();
+    }
+
+    /// Score combining how often and how recently the project at `path`
+    /// was visited, unvisited projects score 0.
+    pub fn frecency(&self, path: &Path) -> f64 {
+        let Some(visits) = self.visits.get(path) else {
+            return 0.0;
+        };
+
+        let weight = match now().saturating_sub(visits.last_visit) {
+            age if age < HOUR => 4.0,
+            age if age < DAY => 2.0,
+            age if age < WEEK => 0.5,
+            _ => 0.25,
+        };
+        visits.count as f64 * weight
+    }
+}
@@ -1 +1,25 @@
+use std::{ffi::OsString, path::PathBuf};
+
+pub mod aliases;
+pub mod cache;
 pub mod config;
+pub mod history;
+
+/// `$<xdg_var>/<name>`, or `$HOME/<home_dir>/<name>` if the variable is
+/// not set. Empty variables count as not set.
+fn xdg_dir(xdg_var: &str, home_dir: &str, name: &str) -> Option<PathBuf> {
+    fn empty_os_string_to_none(os_str: OsString) -> Option<PathBuf> {
+        if os_str.is_empty() {
+            None
+        } else {
+            Some(os_str.into())
+        }
+    }
+
+    std::env::var_os(xdg_var)
+        .and_then(empty_os_string_to_none)
+        .or(std::env::var_os("HOME")
+            .and_then(empty_os_string_to_none)
+            .map(|path| path.join(home_dir)))
+        .map(|path| path.join(name))
+}
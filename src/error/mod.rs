@@ -1,21 +1,148 @@
 #[derive(Debug)]
 pub enum Error {
     CouldNotDetermineConfigLocation(Vec<String>),
+    CouldNotDetermineCacheLocation(Vec<String>),
+    CouldNotDetermineDataLocation(Vec<String>),
+    NoProjectsRoot,
+    UnknownRoot(String),
+    InvalidRootPrefix {
+        prefix: String,
+        reason: String,
+    },
+    ProjectNotFound {
+        query: String,
+        suggestions: Vec<String>,
+    },
+    AmbiguousProject {
+        query: String,
+        candidates: Vec<String>,
+    },
+    NamespaceNotFound(String),
+    InvalidTemplate {
+        template: String,
+        reason: String,
+    },
+    InvalidDetector {
+        name: String,
+        reason: String,
+    },
+    InvalidIgnorePattern {
+        pattern: String,
+        reason: String,
+    },
+    CannotReadRoot {
+        path: std::path::PathBuf,
+        reason: String,
+    },
+    SlugCollisions(usize),
+    InvalidSlugRules(String),
+    InvalidAlias {
+        alias: String,
+        reason: String,
+    },
+    AliasNotFound(String),
+    NotInProject(std::path::PathBuf),
+    BookmarkNotFound {
+        slug: String,
+        bookmark: String,
+    },
+    DirectoryNotFound(std::path::PathBuf),
+    OutsideProject(String),
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
             Error::CouldNotDetermineConfigLocation(tried_locations) => {
-                write!(
+                writeln!(
                     f,
-                    "Could not determine location for config directory!\nTried:\n"
+                    "Could not determine location for config directory!\nTried:"
                 )?;
                 for loc in tried_locations.iter() {
-                    write!(f, "  - {}\n", loc)?;
+                    writeln!(f, "  - {}", loc)?;
                 }
                 Ok(())
             }
+            Error::CouldNotDetermineCacheLocation(tried_locations) => {
+                writeln!(
+                    f,
+                    "Could not determine location for cache directory!\nTried:"
+                )?;
+                for loc in tried_locations.iter() {
+                    writeln!(f, "  - {}", loc)?;
+                }
+                Ok(())
+            }
+            Error::CouldNotDetermineDataLocation(tried_locations) => {
+                writeln!(
+                    f,
+                    "Could not determine location for data directory!\nTried:"
+                )?;
+                for loc in tried_locations.iter() {
+                    writeln!(f, "  - {}", loc)?;
+                }
+                Ok(())
+            }
+            Error::NoProjectsRoot => write!(
+                f,
+                "No projects root configured!\nPass '--projects-root' or set 'projects-root' in config.yaml"
+            ),
+            Error::UnknownRoot(prefix) => {
+                write!(f, "No project root is mounted at '{}'", prefix)
+            }
+            Error::InvalidRootPrefix { prefix, reason } => {
+                write!(f, "Invalid root prefix '{}': {}", prefix, reason)
+            }
+            Error::ProjectNotFound { query, suggestions } => {
+                write!(f, "No project matches '{}'", query)?;
+                if !suggestions.is_empty() {
+                    write!(f, "\nDid you mean:")?;
+                    for suggestion in suggestions.iter() {
+                        write!(f, "\n  - {}", suggestion)?;
+                    }
+                }
+                Ok(())
+            }
+            Error::AmbiguousProject { query, candidates } => {
+                write!(f, "'{}' matches multiple projects:", query)?;
+                for candidate in candidates.iter() {
+                    write!(f, "\n  - {}", candidate)?;
+                }
+                Ok(())
+            }
+            Error::NamespaceNotFound(slug) => write!(f, "No namespace '{}' found", slug),
+            Error::InvalidTemplate { template, reason } => {
+                write!(f, "Invalid template '{}': {}", template, reason)
+            }
+            Error::InvalidDetector { name, reason } => {
+                write!(f, "Invalid detector '{}': {}", name, reason)
+            }
+            Error::InvalidIgnorePattern { pattern, reason } => {
+                write!(f, "Invalid ignore pattern '{}': {}", pattern, reason)
+            }
+            Error::CannotReadRoot { path, reason } => {
+                write!(f, "Cannot read projects root '{}': {}", path.display(), reason)
+            }
+            Error::SlugCollisions(count) => {
+                write!(f, "Found {} slug collisions", count)
+            }
+            Error::InvalidSlugRules(reason) => write!(f, "Invalid slug rules: {}", reason),
+            Error::InvalidAlias { alias, reason } => {
+                write!(f, "Invalid alias '{}': {}", alias, reason)
+            }
+            Error::AliasNotFound(alias) => write!(f, "No alias '{}' defined", alias),
+            Error::BookmarkNotFound { slug, bookmark } => {
+                write!(f, "Project '{}' has no bookmark '{}'", slug, bookmark)
+            }
+            Error::OutsideProject(query) => {
+                write!(f, "'{}' points outside of the project directory", query)
+            }
+            Error::DirectoryNotFound(path) => {
+                write!(f, "'{}' is not a directory", path.display())
+            }
+            Error::NotInProject(path) => {
+                write!(f, "'{}' is not inside a project", path.display())
+            }
         }
     }
 }
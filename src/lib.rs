@@ -2,4 +2,7 @@ pub mod cli;
 pub mod command;
 pub mod data;
 pub mod error;
+pub mod matcher;
+pub mod output;
 pub mod project;
+pub mod sort;
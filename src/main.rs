@@ -3,11 +3,12 @@ use clap::Parser;
 
 use project_manager::cli::{self, Cli};
 use project_manager::command;
-use project_manager::data::config;
+use project_manager::data::config::{self, Config};
 
 fn main() -> Result<()> {
-    config::try_init_config_dir()?;
+    let config_dir = config::try_init_config_dir()?;
     let cli = Cli::parse();
+    let config = Config::load(&config_dir)?;
 
     match cli.command {
         // Commands that don't require project directory
@@ -15,10 +16,17 @@ fn main() -> Result<()> {
         Some(cli::Commands::SupportedVersion(args)) => command::supported_version(args),
 
         // Commands that require project directory
-        Some(cli::Commands::Cd(args)) => command::cd(args),
-        Some(cli::Commands::ListProjects(args)) => command::list_projects(args),
-        Some(cli::Commands::New(args)) => command::new(args),
-        Some(cli::Commands::Clone(args)) => command::clone(args),
+        Some(cli::Commands::Cd(args)) => command::cd(args, &config),
+        Some(cli::Commands::ListProjects(args)) => command::list_projects(args, &config),
+        Some(cli::Commands::New(args)) => command::new(args, &config),
+        Some(cli::Commands::Clone(args)) => command::clone(args, &config),
+        Some(cli::Commands::Reindex(args)) => command::reindex(args, &config),
+        Some(cli::Commands::Tree(args)) => command::tree(args, &config),
+        Some(cli::Commands::Info(args)) => command::info(args, &config),
+        Some(cli::Commands::Check(args)) => command::check(args, &config),
+        Some(cli::Commands::Alias(args)) => command::alias(args, &config),
+        Some(cli::Commands::Where(args)) => command::where_(args, &config),
+        Some(cli::Commands::Prompt(args)) => command::prompt(args, &config),
         None => Ok(()),
     }
 }
@@ -0,0 +1,421 @@
+use crate::error::Error;
+use crate::project::{is_inside, SlugPath, MEMBER_SEPARATOR, PATH_SEPARATOR};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// Maximum number of slugs listed in "did you mean" suggestions.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Points the best fuzzy match has to score above the runner-up to win,
+/// closer matches are reported as ambiguous.
+const FUZZY_MARGIN: i64 = 5;
+
+/// How a slug matched the query, better kinds sort first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchKind {
+    /// The query is the full slug or one of the aliases of the project
+    Exact,
+    /// The query equals the last namespace segments of the slug,
+    /// e.g. `api` for `work.backend.api` or `core` for `work.repo:core`
+    Suffix,
+    /// The characters of the query appear in order in the slug
+    Fuzzy,
+}
+
+pub struct Match<'a> {
+    slug_path: &'a SlugPath,
+    kind: MatchKind,
+    score: i64,
+}
+
+impl<'a> Match<'a> {
+    pub fn slug_path(&self) -> &'a SlugPath {
+        self.slug_path
+    }
+
+    pub fn kind(&self) -> MatchKind {
+        self.kind
+    }
+
+    pub fn score(&self) -> i64 {
+        self.score
+    }
+}
+
+/// All slugs matching `query`, best match first.
+pub fn rank<'a>(query: &str, slug_paths: &'a [SlugPath]) -> Vec<Match<'a>> {
+    let mut matches: Vec<Match> = slug_paths
+        .iter()
+        .filter_map(|slug_path| match_slug(query, slug_path))
+        .collect();
+
+    matches.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then(b.score.cmp(&a.score))
+            .then(a.slug_path.slug().len().cmp(&b.slug_path.slug().len()))
+            .then(a.slug_path.slug().cmp(b.slug_path.slug()))
+    });
+    matches
+}
+
+/// Resolve the argument of `cd` to a project and a directory relative to
+/// it, empty for the project itself.
+///
+/// Besides a project, `query` can name a bookmark from the project file as
+/// `slug:bookmark` and continue with a subdirectory as `slug/sub/dir`.
+/// Slugs never contain [`PATH_SEPARATOR`], so the query is split at the
+/// first one. The subdirectory must stay inside the project. Exact slugs
+/// of sub-projects take precedence over bookmarks of the same name.
+pub fn resolve_target<'a>(
+    query: &str,
+    slug_paths: &'a [SlugPath],
+) -> Result<(&'a SlugPath, PathBuf), Error> {
+    let (project, subpath) = query.split_once(PATH_SEPARATOR).unwrap_or((query, ""));
+    if !is_inside(Path::new(subpath)) {
+        return Err(Error::OutsideProject(query.to_string()));
+    }
+
+    let (slug_path, mut path) = resolve_bookmark(project, slug_paths)?;
+    path.extend(
+        Path::new(subpath)
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_))),
+    );
+    Ok((slug_path, path))
+}
+
+fn resolve_bookmark<'a>(
+    query: &str,
+    slug_paths: &'a [SlugPath],
+) -> Result<(&'a SlugPath, PathBuf), Error> {
+    let resolved = resolve(query, slug_paths);
+    if let Ok(slug_path) = resolved {
+        if match_slug(query, slug_path).is_some_and(|m| m.kind == MatchKind::Exact) {
+            return Ok((slug_path, PathBuf::new()));
+        }
+    }
+
+    if let Some((project, bookmark)) = query.rsplit_once(MEMBER_SEPARATOR) {
+        if let Ok(slug_path) = resolve(project, slug_paths) {
+            match slug_path.metadata().bookmark(bookmark) {
+                Some(path) => return Ok((slug_path, path.to_path_buf())),
+                None if resolved.is_err() => {
+                    return Err(Error::BookmarkNotFound {
+                        slug: slug_path.slug().to_string(),
+                        bookmark: bookmark.to_string(),
+                    })
+                }
+                None => {}
+            }
+        }
+    }
+
+    resolved.map(|slug_path| (slug_path, PathBuf::new()))
+}
+
+/// The project containing the canonical `path` and `path` relative to its
+/// directory, the innermost project if they are nested.
+///
+/// `roots` maps the projects roots to their canonical paths. Projects
+/// reached through a symlink are compared by their stored canonical path,
+/// others below a root with the root replaced and the rest are
+/// canonicalized, so the lookup does not touch the file system for every
+/// project. Symlinks to other projects only win if their target is not
+/// listed.
+pub fn enclosing<'a>(
+    path: &Path,
+    slug_paths: &'a [SlugPath],
+    roots: &[(PathBuf, PathBuf)],
+) -> Option<(&'a SlugPath, PathBuf)> {
+    slug_paths
+        .iter()
+        .filter_map(|slug_path| {
+            let project_path = canonical_path(slug_path, roots)?;
+            let subpath = path.strip_prefix(&project_path).ok()?.to_path_buf();
+            let depth = project_path.components().count();
+            Some((slug_path, depth, subpath))
+        })
+        .max_by_key(|(slug_path, depth, _)| (*depth, !slug_path.is_link()))
+        .map(|(slug_path, _, subpath)| (slug_path, subpath))
+}
+
+fn canonical_path(slug_path: &SlugPath, roots: &[(PathBuf, PathBuf)]) -> Option<PathBuf> {
+    if let Some(canonical) = slug_path.canonical_path() {
+        return Some(canonical.to_path_buf());
+    }
+    let path = slug_path.path();
+    for (root, canonical_root) in roots {
+        if let Ok(relative) = path.strip_prefix(root) {
+            return Some(canonical_root.join(relative));
+        }
+    }
+    fs::canonicalize(path).ok()
+}
+
+/// Resolve `query` to exactly one project.
+///
+/// Exact matches win over suffix matches, which win over fuzzy matches.
+/// Symlinks to other projects only match exactly, so they never
+/// make a query ambiguous. Among fuzzy matches, the best one wins if it
+/// scores at least [`FUZZY_MARGIN`] above the runner-up.
+/// If the best kind of match is not unique or nothing matches at all, an
+/// error listing the candidates is returned.
+pub fn resolve<'a>(query: &str, slug_paths: &'a [SlugPath]) -> Result<&'a SlugPath, Error> {
+    let matches = rank(query, slug_paths);
+
+    let Some(best) = matches.first() else {
+        return Err(Error::ProjectNotFound {
+            query: query.to_string(),
+            suggestions: suggest(query, slug_paths),
+        });
+    };
+
+    let candidates: Vec<&Match> = matches.iter().filter(|m| m.kind == best.kind).collect();
+    let unique = match candidates.get(1) {
+        None => true,
+        Some(runner_up) => {
+            best.kind == MatchKind::Fuzzy && best.score - runner_up.score >= FUZZY_MARGIN
+        }
+    };
+    if unique {
+        return Ok(best.slug_path);
+    }
+
+    Err(Error::AmbiguousProject {
+        query: query.to_string(),
+        candidates: candidates
+            .iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|m| m.slug_path.to_string())
+            .collect(),
+    })
+}
+
+fn match_slug<'a>(query: &str, slug_path: &'a SlugPath) -> Option<Match<'a>> {
+    let slug = slug_path.slug();
+    let separators = [slug_path.separator(), MEMBER_SEPARATOR];
+    let is_alias_name = || {
+        slug_path
+            .aliases()
+            .iter()
+            .any(|alias| alias.as_ref() == query)
+    };
+    let (kind, score) = if slug == query || is_alias_name() {
+        (MatchKind::Exact, 0)
+    } else if !slug_path.is_link()
+        && slug
+            .strip_suffix(query)
+            .is_some_and(|rest| rest.ends_with(separators))
+    {
+        (MatchKind::Suffix, 0)
+    } else if slug_path.is_link() {
+        return None;
+    } else {
+        (MatchKind::Fuzzy, fuzzy_score(query, slug, &separators)?)
+    };
+
+    Some(Match {
+        slug_path,
+        kind,
+        score,
+    })
+}
+
+/// Score `query` as a case insensitive subsequence of `slug`.
+///
+/// Consecutive characters and characters at the start of a slug segment
+/// or word are rewarded, gaps between matched characters are penalized.
+/// Segments are delimited by `separators`.
+fn fuzzy_score(query: &str, slug: &str, separators: &[char]) -> Option<i64> {
+    if query.is_empty() {
+        return None;
+    }
+
+    let slug: Vec<char> = slug.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for c in query.chars().flat_map(char::to_lowercase) {
+        let found = position + slug[position..].iter().position(|&s| s == c)?;
+
+        score += 1;
+        if found == 0
+            || separators.contains(&slug[found - 1])
+            || matches!(slug[found - 1], '-' | '_')
+        {
+            score += 8;
+        }
+        match previous {
+            Some(previous) if previous + 1 == found => score += 5,
+            Some(previous) => score -= (found - previous - 1).min(5) as i64,
+            None => score -= found.min(5) as i64,
+        }
+
+        previous = Some(found);
+        position = found + 1;
+    }
+
+    Some(score)
+}
+
+/// Slugs close to `query` by edit distance, either as a whole or by their
+/// last segment.
+fn suggest(query: &str, slug_paths: &[SlugPath]) -> Vec<String> {
+    let max_distance = (query.chars().count() / 3).max(2);
+
+    let mut suggestions: Vec<(usize, String)> = slug_paths
+        .iter()
+        .filter_map(|slug_path| {
+            let slug = slug_path.slug();
+            let last_segment = slug
+                .rsplit([slug_path.separator(), MEMBER_SEPARATOR])
+                .next()
+                .unwrap_or(slug);
+            let distance = edit_distance(query, slug).min(edit_distance(query, last_segment));
+            (distance <= max_distance).then(|| (distance, slug.to_string()))
+        })
+        .collect();
+
+    suggestions.sort();
+    suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, slug)| slug)
+        .collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Detector, DetectorConfig, TreeBuilder};
+
+    /// Slug paths of projects with the given dotted slugs.
+    fn slug_paths(slugs: &[&str]) -> Vec<SlugPath> {
+        slugs
+            .iter()
+            .fold(TreeBuilder::default(), |tree, slug| tree.project(slug, &[]))
+            .build()
+            .build_project_slugs()
+    }
+
+    fn resolved(query: &str, slug_paths: &[SlugPath]) -> Result<String, Error> {
+        resolve(query, slug_paths).map(|slug_path| slug_path.slug().to_string())
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_segment_starts() {
+        let separators = ['.', MEMBER_SEPARATOR];
+        let score = |query, slug| fuzzy_score(query, slug, &separators);
+        assert!(score("ab", "alpha.beta") > score("ab", "cabin"));
+        assert!(score("api", "work.api") > score("api", "work.apxi"));
+        assert_eq!(score("WB", "work.backend"), score("wb", "work.backend"));
+        assert_eq!(score("ba", "abc"), None);
+        assert_eq!(score("", "abc"), None);
+    }
+
+    #[test]
+    fn rank_orders_by_kind() {
+        let slug_paths = slug_paths(&["work.api-gateway", "work.backend.api", "api"]);
+        let ranked: Vec<(&str, MatchKind)> = rank("api", &slug_paths)
+            .iter()
+            .map(|m| (m.slug_path().slug(), m.kind()))
+            .collect();
+        assert_eq!(
+            ranked,
+            [
+                ("api", MatchKind::Exact),
+                ("work.backend.api", MatchKind::Suffix),
+                ("work.api-gateway", MatchKind::Fuzzy),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_prefers_exact_and_suffix_matches() {
+        let slug_paths = slug_paths(&["work.backend.api", "oss.api", "work.apis"]);
+        assert_eq!(
+            resolved("work.backend.api", &slug_paths).unwrap(),
+            "work.backend.api"
+        );
+        assert_eq!(
+            resolved("backend.api", &slug_paths).unwrap(),
+            "work.backend.api"
+        );
+        assert!(matches!(
+            resolved("api", &slug_paths),
+            Err(Error::AmbiguousProject { candidates, .. })
+                if candidates == ["oss.api", "work.backend.api"]
+        ));
+    }
+
+    #[test]
+    fn resolve_picks_clear_fuzzy_winner() {
+        let slug_paths = slug_paths(&["work.backend", "tools.wordbook"]);
+        assert_eq!(resolved("wb", &slug_paths).unwrap(), "work.backend");
+    }
+
+    #[test]
+    fn resolve_reports_close_fuzzy_matches() {
+        let slug_paths = slug_paths(&["work.backend", "work.billing"]);
+        assert!(matches!(
+            resolved("wb", &slug_paths),
+            Err(Error::AmbiguousProject { candidates, .. }) if candidates.len() == 2
+        ));
+    }
+
+    #[test]
+    fn resolve_suggests_similar_slugs() {
+        let slug_paths = slug_paths(&["work.backend", "oss.ripgrep"]);
+        assert!(matches!(
+            resolved("ripgerp", &slug_paths),
+            Err(Error::ProjectNotFound { suggestions, .. }) if suggestions == ["oss.ripgrep"]
+        ));
+    }
+
+    #[test]
+    fn enclosing_agrees_with_resolve_on_colliding_slugs() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in ["My App", "my-app"] {
+            fs::create_dir(root.path().join(dir)).unwrap();
+            fs::write(root.path().join(dir).join("Makefile"), "").unwrap();
+        }
+        let detector = Detector::with_config(root.path().to_path_buf(), DetectorConfig::default());
+        let slug_paths = detector.detect().unwrap().build_project_slugs();
+        let roots = [(
+            root.path().to_path_buf(),
+            fs::canonicalize(root.path()).unwrap(),
+        )];
+
+        for (dir, slug) in [("My App", "my-app-2"), ("my-app", "my-app")] {
+            let path = fs::canonicalize(root.path().join(dir)).unwrap().join("src");
+            let (slug_path, subpath) = enclosing(&path, &slug_paths, &roots).unwrap();
+            assert_eq!(
+                (slug_path.slug(), subpath.as_path()),
+                (slug, Path::new("src"))
+            );
+            let resolved = resolve(slug_path.slug(), &slug_paths).unwrap();
+            assert_eq!(resolved.path(), root.path().join(dir));
+        }
+    }
+}
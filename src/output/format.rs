@@ -0,0 +1,334 @@
+use crate::error::Error;
+use crate::project::SlugPath;
+use std::io::{self, Write};
+
+/// Machine readable representations of a project listing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// One slug per line
+    Plain,
+    /// Array of project objects
+    Json,
+    /// One project object per line
+    Jsonl,
+    /// Tab separated columns without header
+    Tsv,
+    /// Comma separated columns with header
+    Csv,
+}
+
+/// Columns of the `tsv` and `csv` formats.
+const COLUMNS: &[Field] = &[
+    Field::Slug,
+    Field::Name,
+    Field::Path,
+    Field::Kinds,
+    Field::Namespace,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Slug,
+    Name,
+    Path,
+    Kind,
+    Kinds,
+    Namespace,
+    Description,
+    Version,
+    Tags,
+    Aliases,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Field> {
+        match name {
+            "slug" => Some(Field::Slug),
+            "name" => Some(Field::Name),
+            "path" => Some(Field::Path),
+            "kind" => Some(Field::Kind),
+            "kinds" => Some(Field::Kinds),
+            "namespace" => Some(Field::Namespace),
+            "description" => Some(Field::Description),
+            "version" => Some(Field::Version),
+            "tags" => Some(Field::Tags),
+            "aliases" => Some(Field::Aliases),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Field::Slug => "slug",
+            Field::Name => "name",
+            Field::Path => "path",
+            Field::Kind => "kind",
+            Field::Kinds => "kinds",
+            Field::Namespace => "namespace",
+            Field::Description => "description",
+            Field::Version => "version",
+            Field::Tags => "tags",
+            Field::Aliases => "aliases",
+        }
+    }
+
+    fn value(&self, slug_path: &SlugPath) -> String {
+        match self {
+            Field::Slug => slug_path.slug().to_string(),
+            Field::Name => slug_path.name().to_string(),
+            Field::Path => slug_path.fmt_path(),
+            Field::Kind => slug_path
+                .kinds()
+                .primary()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            Field::Kinds => slug_path.kinds().to_string(),
+            Field::Namespace => slug_path.namespace(),
+            Field::Description => slug_path
+                .metadata()
+                .description()
+                .unwrap_or_default()
+                .to_string(),
+            Field::Version => slug_path
+                .metadata()
+                .version()
+                .unwrap_or_default()
+                .to_string(),
+            Field::Tags => slug_path.metadata().tags().join(","),
+            Field::Aliases => slug_path.aliases().join(","),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+/// User defined line format like `{slug}\t{path}`.
+///
+/// Placeholders are `{slug}`, `{name}`, `{path}`, `{kind}` (the primary
+/// kind), `{kinds}`, `{namespace}`, `{description}`, `{version}`,
+/// `{tags}` and `{aliases}`, `{{` and `}}` produce literal braces. The
+/// escapes `\t`, `\n` and `\\` are expanded, so templates can be passed
+/// from the shell without quoting tricks.
+#[derive(Clone)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl std::str::FromStr for Template {
+    type Err = Error;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| Error::InvalidTemplate {
+            template: template.to_string(),
+            reason,
+        };
+
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => literal.push('\t'),
+                    Some('n') => literal.push('\n'),
+                    Some('\\') => literal.push('\\'),
+                    Some(other) => {
+                        literal.push('\\');
+                        literal.push(other);
+                    }
+                    None => literal.push('\\'),
+                },
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(invalid("unclosed '{'".to_string())),
+                        }
+                    }
+                    let field = Field::parse(&name)
+                        .ok_or_else(|| invalid(format!("unknown placeholder '{{{}}}'", name)))?;
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field(field));
+                }
+                '}' => return Err(invalid("unmatched '}'".to_string())),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Template { segments })
+    }
+}
+
+impl Template {
+    pub fn render(&self, slug_path: &SlugPath) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => literal.clone(),
+                Segment::Field(field) => field.value(slug_path),
+            })
+            .collect()
+    }
+}
+
+pub fn write_template(
+    out: &mut impl Write,
+    slug_paths: &[SlugPath],
+    template: &Template,
+) -> io::Result<()> {
+    for slug_path in slug_paths {
+        writeln!(out, "{}", template.render(slug_path))?;
+    }
+    Ok(())
+}
+
+pub fn write_projects(
+    out: &mut impl Write,
+    slug_paths: &[SlugPath],
+    format: Format,
+) -> io::Result<()> {
+    match format {
+        Format::Plain => {
+            for slug_path in slug_paths {
+                writeln!(out, "{}", slug_path)?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, slug_paths)?;
+            writeln!(out)?;
+        }
+        Format::Jsonl => {
+            for slug_path in slug_paths {
+                serde_json::to_writer(&mut *out, slug_path)?;
+                writeln!(out)?;
+            }
+        }
+        Format::Tsv => {
+            for slug_path in slug_paths {
+                let values: Vec<String> = COLUMNS
+                    .iter()
+                    .map(|field| field.value(slug_path).replace(['\t', '\n'], " "))
+                    .collect();
+                writeln!(out, "{}", values.join("\t"))?;
+            }
+        }
+        Format::Csv => {
+            let header: Vec<&str> = COLUMNS.iter().map(Field::name).collect();
+            writeln!(out, "{}", header.join(","))?;
+            for slug_path in slug_paths {
+                let values: Vec<String> = COLUMNS
+                    .iter()
+                    .map(|field| csv_escape(&field.value(slug_path)))
+                    .collect();
+                writeln!(out, "{}", values.join(","))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::TreeBuilder;
+
+    fn segments(template: &str) -> Vec<Segment> {
+        template.parse::<Template>().unwrap().segments
+    }
+
+    fn reason(template: &str) -> String {
+        match template.parse::<Template>() {
+            Err(Error::InvalidTemplate { reason, .. }) => reason,
+            _ => panic!("'{}' should be invalid", template),
+        }
+    }
+
+    #[test]
+    fn parses_placeholders_and_literals() {
+        assert_eq!(
+            segments("{slug}: {kinds}!"),
+            [
+                Segment::Field(Field::Slug),
+                Segment::Literal(": ".to_string()),
+                Segment::Field(Field::Kinds),
+                Segment::Literal("!".to_string()),
+            ]
+        );
+        assert_eq!(segments(""), []);
+    }
+
+    #[test]
+    fn expands_escapes() {
+        assert_eq!(
+            segments(r"{slug}\t{path}\n\\\x"),
+            [
+                Segment::Field(Field::Slug),
+                Segment::Literal("\t".to_string()),
+                Segment::Field(Field::Path),
+                Segment::Literal("\n\\\\x".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn doubled_braces_are_literal() {
+        assert_eq!(
+            segments("{{{name}}}"),
+            [
+                Segment::Literal("{".to_string()),
+                Segment::Field(Field::Name),
+                Segment::Literal("}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_placeholders() {
+        assert_eq!(reason("{slug"), "unclosed '{'");
+        assert_eq!(reason("{owner}"), "unknown placeholder '{owner}'");
+        assert_eq!(reason("slug}"), "unmatched '}'");
+    }
+
+    #[test]
+    fn renders_fields() {
+        let root = TreeBuilder::default()
+            .project_in("work.api", "API", &["cargo", "nix"])
+            .build();
+        let slug_paths = root.build_project_slugs();
+        let template: Template = "{slug} {name} ({kind}; {kinds}) in {namespace}"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            template.render(&slug_paths[0]),
+            "work.api API (cargo; cargo,nix) in work"
+        );
+    }
+}
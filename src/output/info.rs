@@ -0,0 +1,150 @@
+use crate::project::{RepositoryKind, SlugPath};
+use serde::Serialize;
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Where a path is located in the project tree, printed by `where`.
+#[derive(Serialize)]
+pub struct Location<'a> {
+    pub slug: &'a str,
+    pub namespaces: &'a [Arc<str>],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<&'a str>,
+    /// Directory of the project
+    pub root: &'a Path,
+    /// The path relative to `root`, empty for the root itself
+    pub subpath: PathBuf,
+}
+
+impl<'a> Location<'a> {
+    pub fn new(slug_path: &'a SlugPath, subpath: PathBuf) -> Self {
+        Location {
+            slug: slug_path.slug(),
+            namespaces: slug_path.namespaces(),
+            parent: slug_path.parent(),
+            root: slug_path.path(),
+            subpath,
+        }
+    }
+}
+
+/// Print `location` as aligned `Key: value` lines like [`write_info`].
+pub fn write_location(
+    out: &mut impl Write,
+    location: &Location,
+    separator: char,
+) -> io::Result<()> {
+    let mut lines: Vec<(&str, String)> = vec![("Slug", location.slug.to_string())];
+    if !location.namespaces.is_empty() {
+        lines.push((
+            "Namespace",
+            location.namespaces.join(&separator.to_string()),
+        ));
+    }
+    if let Some(parent) = location.parent {
+        lines.push(("Member of", parent.to_string()));
+    }
+    lines.push(("Root", location.root.display().to_string()));
+    let subpath = if location.subpath.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        location.subpath.display().to_string()
+    };
+    lines.push(("Subpath", subpath));
+    write_lines(out, lines)
+}
+
+/// Print the details of a single project as aligned `Key: value` lines,
+/// unknown values are left out.
+pub fn write_info(out: &mut impl Write, slug_path: &SlugPath) -> io::Result<()> {
+    let mut lines: Vec<(&str, String)> = vec![
+        ("Slug", slug_path.slug().to_string()),
+        ("Name", slug_path.name().to_string()),
+        ("Path", slug_path.fmt_path()),
+        ("Kinds", slug_path.kinds().to_string()),
+    ];
+    if !slug_path.namespaces().is_empty() {
+        lines.push(("Namespace", slug_path.namespace()));
+    }
+    if let Some(parent) = slug_path.parent() {
+        lines.push(("Member of", parent.to_string()));
+    }
+    if let Some(links_to) = slug_path.links_to() {
+        lines.push(("Link to", links_to.to_string()));
+    }
+
+    let metadata = slug_path.metadata();
+    if let Some(name) = metadata.name() {
+        let package = match metadata.version() {
+            Some(version) => format!("{} {}", name, version),
+            None => name.to_string(),
+        };
+        lines.push(("Package", package));
+    }
+    if let Some(module) = metadata.module() {
+        lines.push(("Module", module.to_string()));
+    }
+    if let Some(title) = metadata.title() {
+        lines.push(("Title", title.to_string()));
+    }
+    if let Some(description) = metadata.description() {
+        lines.push(("Description", description.to_string()));
+    }
+    if !slug_path.aliases().is_empty() {
+        lines.push(("Aliases", slug_path.aliases().join(", ")));
+    }
+    if !metadata.tags().is_empty() {
+        lines.push(("Tags", metadata.tags().join(", ")));
+    }
+    if let Some(generator) = metadata.generator() {
+        lines.push(("Generator", generator.to_string()));
+    }
+    if let Some(publisher) = metadata.publisher() {
+        lines.push(("Publisher", publisher.to_string()));
+    }
+    for (name, path) in metadata.bookmarks() {
+        lines.push(("Bookmark", format!("{} ({})", name, path.display())));
+    }
+
+    if let Some(git) = slug_path.git() {
+        let repository = match (git.kind(), slug_path.worktree_of(), git.main_repository()) {
+            (RepositoryKind::Worktree, Some(main), _) => format!("worktree of {}", main),
+            (RepositoryKind::Worktree, None, Some(main)) => {
+                format!("worktree of {}", main.display())
+            }
+            (RepositoryKind::Worktree, None, None) => "worktree".to_string(),
+            (RepositoryKind::Bare, _, _) => "bare repository".to_string(),
+            (RepositoryKind::Repository, _, _) => "repository".to_string(),
+        };
+        lines.push(("Git", repository));
+        for worktree in git.worktrees() {
+            lines.push(("Worktree", worktree.display().to_string()));
+        }
+        for submodule in git.submodules() {
+            let value = match submodule.url() {
+                Some(url) => format!("{} ({})", submodule.path().display(), url),
+                None => submodule.path().display().to_string(),
+            };
+            lines.push(("Submodule", value));
+        }
+    }
+
+    write_lines(out, lines)
+}
+
+fn write_lines(out: &mut impl Write, lines: Vec<(&str, String)>) -> io::Result<()> {
+    let width = lines.iter().map(|(key, _)| key.len()).max().unwrap_or(0) + 1;
+    for (key, value) in lines {
+        writeln!(
+            out,
+            "{:width$} {}",
+            format!("{}:", key),
+            value,
+            width = width
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,3 @@
+pub mod format;
+pub mod info;
+pub mod tree;
@@ -0,0 +1,237 @@
+use crate::project::{GitInfo, NamespaceItem, Project, ProjectKind, RepositoryKind, SubNamespace};
+use std::io::{self, IsTerminal, Write};
+
+const NAMESPACE_ICON: &str = "\u{f07b}";
+const NAMESPACE_COLOR: &str = "1;34";
+const PROJECT_ICON: &str = "\u{f1b2}";
+const PROJECT_COLOR: &str = "35";
+const DIM_COLOR: &str = "2";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Colorize if stdout is a terminal and `NO_COLOR` is not set
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(&self) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// Options for rendering a namespace tree.
+pub struct TreeStyle {
+    /// Number of namespace levels to expand, `None` expands everything
+    pub depth: Option<usize>,
+    /// Prefix items with Nerd Font icons
+    pub icons: bool,
+    pub color: bool,
+}
+
+/// Nerd Font glyph for projects of `kind`.
+fn kind_icon(kind: &ProjectKind) -> &'static str {
+    match kind {
+        ProjectKind::Git => "\u{e702}",
+        ProjectKind::Zig => "\u{e6a9}",
+        ProjectKind::Cargo => "\u{e7a8}",
+        ProjectKind::Meson | ProjectKind::Make | ProjectKind::CMake => "\u{f0ad}",
+        ProjectKind::Node => "\u{e718}",
+        ProjectKind::Nix => "\u{f313}",
+        ProjectKind::Go => "\u{e627}",
+        ProjectKind::ProjectFile | ProjectKind::Custom(_) => PROJECT_ICON,
+    }
+}
+
+/// ANSI SGR parameters used for projects of `kind`.
+fn kind_color(kind: &ProjectKind) -> &'static str {
+    match kind {
+        ProjectKind::Git => "31",
+        ProjectKind::Zig => "33",
+        ProjectKind::Cargo => "91",
+        ProjectKind::Meson => "32",
+        ProjectKind::Make => "37",
+        ProjectKind::CMake => "36",
+        ProjectKind::Node => "92",
+        ProjectKind::Nix => "34",
+        ProjectKind::Go => "96",
+        ProjectKind::ProjectFile | ProjectKind::Custom(_) => PROJECT_COLOR,
+    }
+}
+
+impl TreeStyle {
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", color, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn icon(&self, icon: &str) -> String {
+        if self.icons {
+            format!("{} ", icon)
+        } else {
+            String::new()
+        }
+    }
+}
+
+fn fmt_count(count: usize, links: usize) -> String {
+    let projects = match count {
+        0 if links == 0 => return "empty".to_string(),
+        1 => "1 project".to_string(),
+        n => format!("{} projects", n),
+    };
+    match links {
+        0 => projects,
+        1 if count == 0 => "1 link".to_string(),
+        n if count == 0 => format!("{} links", n),
+        1 => format!("{}, 1 link", projects),
+        n => format!("{}, {} links", projects, n),
+    }
+}
+
+fn fmt_namespace_details(namespace: &SubNamespace, count: usize, links: usize) -> String {
+    if namespace.is_truncated() {
+        format!("({}, truncated)", fmt_count(count, links))
+    } else {
+        format!("({})", fmt_count(count, links))
+    }
+}
+
+/// Kinds of `project`, followed by the layout of git repositories that
+/// are not plain working directories.
+fn fmt_kinds(project: &Project) -> String {
+    match project.git().map(GitInfo::kind) {
+        Some(RepositoryKind::Worktree) => format!("[{}] worktree", project.kinds()),
+        Some(RepositoryKind::Bare) => format!("[{}] bare", project.kinds()),
+        _ => format!("[{}]", project.kinds()),
+    }
+}
+
+/// Number of symlinks to other projects in `items` and all nested namespaces.
+fn count_links(items: &[NamespaceItem]) -> usize {
+    items
+        .iter()
+        .map(|item| match item {
+            NamespaceItem::Project(project) => usize::from(project.links_to().is_some()),
+            NamespaceItem::Namespace(namespace) => count_links(namespace.items()),
+        })
+        .sum()
+}
+
+/// Render `items` below a `title` line, drawing the namespace hierarchy
+/// with box characters.
+///
+/// Namespaces without any projects are collapsed into a single line.
+pub fn write_tree(
+    out: &mut impl Write,
+    title: &str,
+    project_count: usize,
+    items: &[NamespaceItem],
+    style: &TreeStyle,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{} {}",
+        style.paint(NAMESPACE_COLOR, title),
+        style.paint(
+            DIM_COLOR,
+            &format!("({})", fmt_count(project_count, count_links(items)))
+        )
+    )?;
+    write_items(out, items, "", 1, style)
+}
+
+/// Render the workspace `members` of a project as `:slug`.
+fn write_members(
+    out: &mut impl Write,
+    members: &[Project],
+    prefix: &str,
+    style: &TreeStyle,
+) -> io::Result<()> {
+    for (i, member) in members.iter().enumerate() {
+        let branch = if i + 1 == members.len() {
+            "└── "
+        } else {
+            "├── "
+        };
+        let kind = member.kinds().primary();
+        writeln!(
+            out,
+            "{}{}{}{} {}",
+            prefix,
+            branch,
+            style.icon(kind.map_or(PROJECT_ICON, kind_icon)),
+            style.paint(
+                kind.map_or(PROJECT_COLOR, kind_color),
+                &format!(":{}", member.slug())
+            ),
+            style.paint(DIM_COLOR, &fmt_kinds(member)),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_items(
+    out: &mut impl Write,
+    items: &[NamespaceItem],
+    prefix: &str,
+    depth: usize,
+    style: &TreeStyle,
+) -> io::Result<()> {
+    for (i, item) in items.iter().enumerate() {
+        let last = i + 1 == items.len();
+        let branch = if last { "└── " } else { "├── " };
+
+        match item {
+            NamespaceItem::Project(project) => {
+                let kind = project.kinds().primary();
+                let details = match project.links_to() {
+                    Some(target) => format!("-> {}", target.display()),
+                    None => fmt_kinds(project),
+                };
+                writeln!(
+                    out,
+                    "{}{}{}{} {}",
+                    prefix,
+                    branch,
+                    style.icon(kind.map_or(PROJECT_ICON, kind_icon)),
+                    style.paint(kind.map_or(PROJECT_COLOR, kind_color), project.slug()),
+                    style.paint(DIM_COLOR, &details),
+                )?;
+
+                let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+                write_members(out, project.members(), &child_prefix, style)?;
+            }
+            NamespaceItem::Namespace(namespace) => {
+                let count = namespace.project_count();
+                let links = count_links(namespace.items());
+                let expand = count + links > 0 && style.depth.is_none_or(|max| depth < max);
+                writeln!(
+                    out,
+                    "{}{}{}{} {}",
+                    prefix,
+                    branch,
+                    style.icon(NAMESPACE_ICON),
+                    style.paint(NAMESPACE_COLOR, namespace.slug()),
+                    style.paint(DIM_COLOR, &fmt_namespace_details(namespace, count, links)),
+                )?;
+
+                if expand {
+                    let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+                    write_items(out, namespace.items(), &child_prefix, depth + 1, style)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
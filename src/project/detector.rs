@@ -0,0 +1,1221 @@
+use super::git::{GitDetector, GitInfo, RepositoryKind};
+use super::ignore::{validate_pattern, IgnoreStack};
+use super::metadata::Metadata;
+use super::project_file::{ProjectFile, PROJECT_FILE_NAME};
+use super::workspace::Workspace;
+use super::{
+    disambiguate, merge_items, qualify, sort_items, Info, NamespaceItem, Project, ProjectKind,
+    ProjectKinds, RootNamespace, SlugRules, SubNamespace, Warning, WarningKind,
+};
+use crate::error::Error;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    ffi::{OsStr, OsString},
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+/// Decides whether a directory is a project.
+///
+/// Implement this trait to recognise additional kinds of projects and add
+/// the detector to a [`DetectorRegistry`].
+pub trait ProjectDetector: std::fmt::Debug + Send + Sync {
+    /// Unique name used to disable or reprioritize the detector
+    fn name(&self) -> &str;
+
+    /// Kind of the projects found by this detector
+    fn kind(&self) -> ProjectKind;
+
+    /// Whether the directory described by `listing` is a project.
+    fn detect(&self, listing: &DirListing) -> bool;
+
+    /// Files in the directory whose content [`ProjectDetector::detect`]
+    /// reads. The project index is rebuilt once one of them changes.
+    fn read_files(&self, _listing: &DirListing) -> Vec<PathBuf> {
+        Vec::new()
+    }
+}
+
+/// Detects projects by the presence of a marker file.
+///
+/// Markers are glob patterns matched against the names of the entries of
+/// a directory. Optionally a matching marker must also contain a given
+/// string, e.g. `[tool.poetry]` in `pyproject.toml`.
+pub struct MarkerDetector {
+    name: Arc<str>,
+    kind: ProjectKind,
+    markers: Vec<String>,
+    matcher: GlobSet,
+    contents: Option<String>,
+}
+
+impl MarkerDetector {
+    /// Detector named after `kind`, matching any of the literal `filenames`.
+    pub fn new(kind: ProjectKind, filenames: &[&str]) -> Self {
+        let markers: Vec<String> = filenames.iter().map(|f| globset::escape(f)).collect();
+        Self::from_globs(kind.as_str(), &markers)
+            .expect("Escaped filenames are valid globs")
+            .with_kind(kind)
+    }
+
+    /// Detector called `name` matching any of the glob patterns `markers`.
+    ///
+    /// Detected projects are of the kind `name` unless changed with
+    /// [`MarkerDetector::with_kind`].
+    pub fn from_globs(name: &str, markers: &[String]) -> Result<Self, Error> {
+        let mut builder = GlobSetBuilder::new();
+        for marker in markers {
+            builder.add(Glob::new(marker).map_err(|err| Error::InvalidDetector {
+                name: name.to_string(),
+                reason: err.to_string(),
+            })?);
+        }
+        let matcher = builder.build().map_err(|err| Error::InvalidDetector {
+            name: name.to_string(),
+            reason: err.to_string(),
+        })?;
+
+        Ok(MarkerDetector {
+            name: name.into(),
+            kind: name.into(),
+            markers: markers.to_vec(),
+            matcher,
+            contents: None,
+        })
+    }
+
+    pub fn with_kind(mut self, kind: ProjectKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Only accept marker files containing `contents`.
+    pub fn with_contents(mut self, contents: String) -> Self {
+        self.contents = Some(contents);
+        self
+    }
+
+    fn marker_matches(&self, marker: &Path) -> bool {
+        match &self.contents {
+            None => true,
+            Some(contents) => fs::read_to_string(marker)
+                .map(|content| content.contains(contents.as_str()))
+                .unwrap_or(false),
+        }
+    }
+}
+
+impl std::fmt::Debug for MarkerDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MarkerDetector")
+            .field("name", &self.name)
+            .field("kind", &self.kind)
+            .field("markers", &self.markers)
+            .field("contents", &self.contents)
+            .finish()
+    }
+}
+
+impl ProjectDetector for MarkerDetector {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> ProjectKind {
+        self.kind.clone()
+    }
+
+    fn detect(&self, listing: &DirListing) -> bool {
+        listing.file_names().any(|filename| {
+            self.matcher.is_match(filename.to_string_lossy().deref())
+                && self.marker_matches(&listing.path().join(filename))
+        })
+    }
+
+    fn read_files(&self, listing: &DirListing) -> Vec<PathBuf> {
+        if self.contents.is_none() {
+            return Vec::new();
+        }
+        listing
+            .file_names()
+            .filter(|filename| self.matcher.is_match(filename.to_string_lossy().deref()))
+            .map(|filename| listing.path().join(filename))
+            .collect()
+    }
+}
+
+/// Ordered set of detectors, a directory is assigned the kinds of all
+/// detectors that match it.
+///
+/// Detectors with a higher priority run first, detectors with equal
+/// priority run in the order they were registered.
+#[derive(Clone, Debug)]
+pub struct DetectorRegistry {
+    entries: Vec<(i32, Arc<dyn ProjectDetector>)>,
+}
+
+impl DetectorRegistry {
+    /// Registry without any detectors.
+    pub fn empty() -> Self {
+        DetectorRegistry {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Registry with the built-in detectors, all with priority 0.
+    pub fn builtin() -> Self {
+        Self::empty()
+            .register(GitDetector)
+            .register(MarkerDetector::new(ProjectKind::Zig, &["build.zig"]))
+            .register(MarkerDetector::new(ProjectKind::Cargo, &["Cargo.toml"]))
+            .register(MarkerDetector::new(ProjectKind::Meson, &["meson.build"]))
+            .register(MarkerDetector::new(
+                ProjectKind::Make,
+                &["Makefile", "makefile", "GNUmakefile"],
+            ))
+            .register(MarkerDetector::new(ProjectKind::CMake, &["CMakeLists.txt"]))
+            .register(MarkerDetector::new(ProjectKind::Node, &["package.json"]))
+            .register(MarkerDetector::new(
+                ProjectKind::Nix,
+                &["default.nix", "shell.nix", "flake.nix"],
+            ))
+            .register(MarkerDetector::new(ProjectKind::Go, &["go.mod", "go.work"]))
+            .register(MarkerDetector::new(
+                ProjectKind::ProjectFile,
+                &[PROJECT_FILE_NAME],
+            ))
+    }
+
+    /// Add `detector` with priority 0.
+    pub fn register(self, detector: impl ProjectDetector + 'static) -> Self {
+        self.register_with_priority(detector, 0)
+    }
+
+    /// Add `detector`, replacing any detector with the same name.
+    pub fn register_with_priority(
+        mut self,
+        detector: impl ProjectDetector + 'static,
+        priority: i32,
+    ) -> Self {
+        self.entries.retain(|(_, d)| d.name() != detector.name());
+        self.entries.push((priority, Arc::new(detector)));
+        self.sort();
+        self
+    }
+
+    /// Change the priority of the detector called `name`.
+    pub fn set_priority(mut self, name: &str, priority: i32) -> Self {
+        for entry in self.entries.iter_mut().filter(|(_, d)| d.name() == name) {
+            entry.0 = priority;
+        }
+        self.sort();
+        self
+    }
+
+    /// Remove the detector called `name`.
+    pub fn disable(mut self, name: &str) -> Self {
+        self.entries.retain(|(_, d)| d.name() != name);
+        self
+    }
+
+    /// Names of the registered detectors in the order they are run.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(_, d)| d.name())
+    }
+
+    pub fn detectors(&self) -> impl Iterator<Item = &dyn ProjectDetector> {
+        self.entries.iter().map(|(_, d)| d.as_ref())
+    }
+
+    fn sort(&mut self) {
+        self.entries
+            .sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+    }
+}
+
+impl Default for DetectorRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+/// Directory that is scanned for projects.
+///
+/// A root mounted below a prefix places all of its items in a namespace
+/// with that slug, e.g. `work.api` for the project `api`.
+#[derive(Clone, Debug)]
+pub struct ProjectRoot {
+    prefix: Option<Arc<str>>,
+    path: PathBuf,
+}
+
+impl ProjectRoot {
+    pub fn new(path: PathBuf) -> Self {
+        ProjectRoot { prefix: None, path }
+    }
+
+    pub fn mounted(prefix: Arc<str>, path: PathBuf) -> Self {
+        ProjectRoot {
+            prefix: Some(prefix),
+            path,
+        }
+    }
+
+    pub fn prefix(&self) -> Option<&str> {
+        self.prefix.as_deref()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The same root with a path relative to the current directory made
+    /// absolute, symlinks are kept.
+    pub fn absolute(self) -> std::io::Result<Self> {
+        Ok(ProjectRoot {
+            path: std::path::absolute(&self.path)?,
+            ..self
+        })
+    }
+}
+
+impl FromStr for ProjectRoot {
+    type Err = String;
+
+    /// Parse `PREFIX=PATH` or a plain `PATH`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some(("", _)) => Err(format!("Empty prefix in '{}'", s)),
+            Some((_, "")) => Err(format!("Empty path in '{}'", s)),
+            Some((prefix, path)) => Ok(ProjectRoot::mounted(prefix.into(), path.into())),
+            None => Ok(ProjectRoot::new(s.into())),
+        }
+    }
+}
+
+pub struct Detector {
+    roots: Vec<ProjectRoot>,
+    config: DetectorConfig,
+}
+
+/// How symlinks to directories are treated while scanning.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SymlinkPolicy {
+    /// Skip symlinked directories
+    Ignore,
+    /// Follow symlinks, except those pointing to a parent directory
+    #[default]
+    Follow,
+    /// Follow symlinks, but no further symlinks below a followed one
+    FollowOnce,
+}
+
+impl SymlinkPolicy {
+    /// Whether a symlink in a directory that was itself reached through a
+    /// symlink (`linked`) is followed.
+    fn follows(self, linked: bool) -> bool {
+        match self {
+            SymlinkPolicy::Ignore => false,
+            SymlinkPolicy::Follow => true,
+            SymlinkPolicy::FollowOnce => !linked,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DetectorConfig {
+    ignore_hidden: bool,
+    ignore_patterns: Vec<String>,
+    symlinks: SymlinkPolicy,
+    max_depth: Option<usize>,
+    namespace_depths: BTreeMap<String, usize>,
+    sub_projects: bool,
+    submodules: bool,
+    slug_rules: SlugRules,
+    detectors: DetectorRegistry,
+}
+
+impl DetectorConfig {
+    pub fn ignore_hidden_files(mut self, ignore: bool) -> Self {
+        self.ignore_hidden = ignore;
+        self
+    }
+
+    pub fn symlinks(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
+    }
+
+    /// Only scan directories up to `depth` levels below the root, deeper
+    /// namespaces are truncated.
+    pub fn max_depth(mut self, depth: Option<usize>) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Scan `depth` levels below the namespace with the fully qualified
+    /// slug `slug`, overriding the maximum depth. A depth of 0 truncates
+    /// the namespace.
+    pub fn namespace_depth(mut self, slug: String, depth: usize) -> Self {
+        self.namespace_depths.insert(slug, depth);
+        self
+    }
+
+    /// List the members of Cargo, npm, pnpm and Go workspaces and meson
+    /// subprojects as sub-projects of the project containing them.
+    pub fn sub_projects(mut self, enable: bool) -> Self {
+        self.sub_projects = enable;
+        self
+    }
+
+    /// List the checked out submodules of git repositories as their
+    /// sub-projects.
+    pub fn submodules(mut self, enable: bool) -> Self {
+        self.submodules = enable;
+        self
+    }
+
+    /// Derive slugs from directory names using `rules`.
+    pub fn slug_rules(mut self, rules: SlugRules) -> Result<Self, Error> {
+        rules.validate()?;
+        self.slug_rules = rules;
+        Ok(self)
+    }
+
+    /// Remaining depth of the namespace `slug`, `inherited` from its
+    /// parent unless overridden.
+    fn depth_of(&self, slug: &str, inherited: Option<usize>) -> Option<usize> {
+        self.namespace_depths.get(slug).copied().or(inherited)
+    }
+
+    /// Skip directories matching the gitignore style `pattern`.
+    ///
+    /// Patterns are matched against the path relative to the project root,
+    /// so `/tmp` only matches at the top level while `tmp` matches at every
+    /// level. A pattern starting with `!` brings back a previously ignored
+    /// directory.
+    pub fn ignore_pattern(mut self, pattern: String) -> Result<Self, Error> {
+        validate_pattern(&pattern)?;
+        self.ignore_patterns.push(pattern);
+        Ok(self)
+    }
+
+    /// Replace the registered detectors.
+    pub fn detectors(mut self, detectors: DetectorRegistry) -> Self {
+        self.detectors = detectors;
+        self
+    }
+
+    /// Add `detector` with the given priority, built-in detectors have
+    /// priority 0.
+    pub fn detector(mut self, detector: impl ProjectDetector + 'static, priority: i32) -> Self {
+        self.detectors = self.detectors.register_with_priority(detector, priority);
+        self
+    }
+
+    /// Remove the detector called `name`.
+    pub fn disable_detector(mut self, name: &str) -> Self {
+        self.detectors = self.detectors.disable(name);
+        self
+    }
+}
+
+impl Default for DetectorConfig {
+    fn default() -> Self {
+        DetectorConfig {
+            ignore_hidden: true,
+            ignore_patterns: vec![],
+            symlinks: SymlinkPolicy::default(),
+            max_depth: None,
+            namespace_depths: BTreeMap::new(),
+            sub_projects: false,
+            submodules: false,
+            slug_rules: SlugRules::default(),
+            detectors: DetectorRegistry::builtin(),
+        }
+    }
+}
+
+impl Detector {
+    pub fn new(root: PathBuf) -> Self {
+        Self::with_config(root, DetectorConfig::default())
+    }
+
+    pub fn with_config(root: PathBuf, config: DetectorConfig) -> Self {
+        Self::with_roots(vec![ProjectRoot::new(root)], config)
+    }
+
+    /// Detector merging the projects of all `roots` into one tree.
+    pub fn with_roots(roots: Vec<ProjectRoot>, config: DetectorConfig) -> Self {
+        Detector { roots, config }
+    }
+
+    /// Identifies the roots and configuration of this detector, detection
+    /// results are only interchangeable between equal fingerprints.
+    pub fn fingerprint(&self) -> String {
+        format!("{:?} {:?}", self.roots, self.config)
+    }
+
+    /// Detect all projects below the roots.
+    ///
+    /// Only unreadable roots are an error, any other path that cannot be
+    /// read is skipped and reported in [`RootNamespace::warnings`].
+    ///
+    /// Projects reached through a symlink to a directory that is detected
+    /// elsewhere as well are marked as links to it. Items sharing a slug
+    /// are renamed and reported in [`RootNamespace::collisions`].
+    pub fn detect(self) -> Result<RootNamespace, Error> {
+        let mut items = Vec::new();
+        let mut scan = Scan::default();
+
+        for root in &self.roots {
+            let cannot_read = |err: std::io::Error| Error::CannotReadRoot {
+                path: root.path.clone(),
+                reason: err.to_string(),
+            };
+            let listing = DirListing::read(&root.path).map_err(cannot_read)?;
+            let metadata = fs::metadata(&root.path).map_err(cannot_read)?;
+
+            let ignores = IgnoreStack::new(&root.path, &self.config.ignore_patterns);
+            let slug = root.prefix.as_deref().unwrap_or_default().to_string();
+            let state = DirState {
+                ancestors: vec![dir_id(&root.path, &metadata)],
+                canonical: fs::canonicalize(&root.path).unwrap_or_else(|_| root.path.clone()),
+                linked: false,
+                remaining_depth: self.config.depth_of(&slug, self.config.max_depth),
+                slug,
+            };
+            let parsed = self.parse_namespace(&listing, &ignores, &state);
+            scan.warnings.extend(listing.warnings());
+            scan.append(parsed.scan);
+
+            let mut root_items = parsed.items;
+            if let Some(prefix) = &root.prefix {
+                let mut namespace = SubNamespace::with_items(
+                    Info {
+                        name: prefix.clone(),
+                        slug: prefix.clone(),
+                        path: root.path.clone(),
+                    },
+                    root_items,
+                );
+                namespace.truncated = parsed.truncated;
+                root_items = vec![NamespaceItem::Namespace(namespace)];
+            }
+
+            merge_items(&mut items, root_items);
+        }
+
+        let rules = &self.config.slug_rules;
+        let mut items = hide_namespaces(items, "", rules);
+        sort_items(&mut items);
+        mark_links(&mut items, &find_links(&scan.projects));
+        mark_worktrees(&mut items, &scan.projects);
+        let mut collisions = Vec::new();
+        disambiguate(&mut items, "", rules, &mut collisions);
+        let separator = rules.namespace_separator();
+
+        Ok(RootNamespace::with_items(
+            Info {
+                name: "Root".into(),
+                slug: "root".into(),
+                path: self
+                    .roots
+                    .into_iter()
+                    .next()
+                    .map(|root| root.path)
+                    .unwrap_or_default(),
+            },
+            items,
+            collisions,
+            scan.warnings,
+            separator,
+            scan.scanned_paths,
+        ))
+    }
+
+    /// Detect all items in the namespace directory `listing`, `ignores` are
+    /// the ignore rules of its parent directory.
+    ///
+    /// Subdirectories are scanned in parallel, the items keep the order of
+    /// the listing. If the maximum depth is reached, the subdirectories are
+    /// not scanned and the namespace is truncated.
+    fn parse_namespace(
+        &self,
+        listing: &DirListing,
+        ignores: &IgnoreStack,
+        state: &DirState,
+    ) -> ParsedNamespace {
+        let mut scan = Scan::default();
+        let (ignores, ignore_file) = ignores.enter(listing.path(), &mut scan.warnings);
+        scan.scanned_paths.push(listing.path().to_path_buf());
+        scan.scanned_paths.extend(ignore_file);
+
+        let subdirs = self.list_dir(listing, &ignores, &mut scan.warnings);
+        if state.remaining_depth == Some(0) {
+            return ParsedNamespace {
+                items: Vec::new(),
+                truncated: !subdirs.is_empty(),
+                scan,
+            };
+        }
+
+        let results: Vec<(Option<NamespaceItem>, Scan)> = subdirs
+            .into_par_iter()
+            .map(|subdir| self.parse_subdir(subdir, &ignores, state))
+            .collect();
+
+        let mut items = Vec::new();
+        for (item, sub_scan) in results {
+            items.extend(item);
+            scan.append(sub_scan);
+        }
+        ParsedNamespace {
+            items,
+            truncated: false,
+            scan,
+        }
+    }
+
+    /// Detect the project or namespace `subdir` of the directory described
+    /// by `state`.
+    fn parse_subdir(
+        &self,
+        subdir: Subdir,
+        ignores: &IgnoreStack,
+        state: &DirState,
+    ) -> (Option<NamespaceItem>, Scan) {
+        let mut scan = Scan::default();
+
+        if subdir.is_symlink && !self.config.symlinks.follows(state.linked) {
+            return (None, scan);
+        }
+        let id = match subdir.id {
+            Some(id) => id,
+            None => match fs::metadata(&subdir.path) {
+                Ok(metadata) => dir_id(&subdir.path, &metadata),
+                Err(err) => {
+                    scan.warnings.push(Warning::from_io(subdir.path, &err));
+                    return (None, scan);
+                }
+            },
+        };
+        if state.ancestors.contains(&id) {
+            scan.warnings
+                .push(Warning::new(subdir.path, WarningKind::SymlinkCycle));
+            return (None, scan);
+        }
+
+        let canonical = if subdir.is_symlink {
+            match fs::canonicalize(&subdir.path) {
+                Ok(canonical) => canonical,
+                Err(err) => {
+                    scan.warnings.push(Warning::from_io(subdir.path, &err));
+                    return (None, scan);
+                }
+            }
+        } else {
+            state
+                .canonical
+                .join(subdir.path.file_name().unwrap_or_default())
+        };
+        let linked = state.linked || subdir.is_symlink;
+
+        // Unreadable directories become empty namespaces, so they still
+        // show up next to the warning.
+        let listing = DirListing::read(&subdir.path).unwrap_or_else(|err| {
+            scan.warnings
+                .push(Warning::from_io(subdir.path.clone(), &err));
+            DirListing::empty(subdir.path.clone())
+        });
+        scan.warnings.append(&mut listing.warnings());
+
+        if let Some(mut project) = self.detect_project(&listing, &mut scan) {
+            project.members = self.detect_members(&project, &listing, &canonical, &mut scan);
+            if linked {
+                project.canonical = Some(canonical.clone());
+            }
+            scan.scanned_paths.push(subdir.path.clone());
+            scan.projects.push(ScannedProject {
+                path: subdir.path,
+                canonical,
+                linked,
+            });
+            return (Some(NamespaceItem::Project(project)), scan);
+        }
+
+        let info = Info::parse_dir_path(&subdir.path, &self.config.slug_rules);
+        let slug = qualify(
+            &state.slug,
+            &info.slug,
+            self.config.slug_rules.namespace_separator(),
+        );
+        let mut ancestors = state.ancestors.clone();
+        ancestors.push(id);
+        let sub_state = DirState {
+            ancestors,
+            canonical,
+            linked,
+            remaining_depth: self
+                .config
+                .depth_of(&slug, state.remaining_depth.map(|depth| depth - 1)),
+            slug,
+        };
+        let parsed = self.parse_namespace(&listing, ignores, &sub_state);
+        scan.append(parsed.scan);
+
+        let mut namespace = SubNamespace::with_items(info, parsed.items);
+        namespace.truncated = parsed.truncated;
+        (Some(NamespaceItem::Namespace(namespace)), scan)
+    }
+
+    fn detect_project(&self, listing: &DirListing, scan: &mut Scan) -> Option<Project> {
+        let mut kinds = ProjectKinds::default();
+        for detector in self.config.detectors.detectors() {
+            // Marker contents can change without touching the directory.
+            scan.scanned_paths.extend(detector.read_files(listing));
+            if detector.detect(listing) {
+                kinds.insert(detector.kind());
+            }
+        }
+
+        if kinds.is_empty() {
+            return None;
+        }
+
+        let git = if kinds.contains(&ProjectKind::Git) {
+            GitInfo::read(listing, &mut scan.scanned_paths)
+        } else {
+            None
+        };
+
+        let rules = &self.config.slug_rules;
+        let mut info = Info::parse_dir_path(listing.path(), rules);
+        // `repo.git` is named `repo`
+        if git.as_ref().map(GitInfo::kind) == Some(RepositoryKind::Bare) {
+            if let Some(name) = info.name.strip_suffix(".git") {
+                info.slug = rules.slugify(name).into();
+            }
+        }
+
+        let mut metadata = Metadata::read(listing, &mut scan.scanned_paths);
+        match ProjectFile::read(listing, rules, &mut scan.scanned_paths) {
+            Ok(Some(mut file)) => {
+                if let Some(slug) = file.slug.take() {
+                    info.slug = slug.into();
+                }
+                if let Some(name) = file.name.take() {
+                    info.name = name.into();
+                }
+                metadata = metadata.with_project_file(file);
+            }
+            Ok(None) => {}
+            Err(reason) => scan.warnings.push(Warning::new(
+                listing.path().join(PROJECT_FILE_NAME),
+                WarningKind::InvalidProjectFile(reason),
+            )),
+        }
+
+        Some(Project {
+            info,
+            kinds,
+            links_to: None,
+            canonical: None,
+            worktree_of: None,
+            members: Vec::new(),
+            git,
+            metadata: Arc::new(metadata),
+        })
+    }
+
+    /// Sub-projects of `project`, whose directory is described by
+    /// `listing` and has the canonical path `canonical`.
+    ///
+    /// These are the git worktrees inside the project directory and,
+    /// if enabled, checked out submodules and workspace members. Members
+    /// are named after their directory, their kinds are detected like the
+    /// kinds of any other project, falling back to the kind of the
+    /// workspace.
+    fn detect_members(
+        &self,
+        project: &Project,
+        listing: &DirListing,
+        canonical: &Path,
+        scan: &mut Scan,
+    ) -> Vec<Project> {
+        let mut candidates: Vec<(PathBuf, ProjectKind)> = Vec::new();
+        if let Some(git) = &project.git {
+            // git registers worktrees by their canonical path, the listing
+            // may be reached through a symlink or a relative root.
+            candidates.extend(git.worktrees().iter().filter_map(|worktree| {
+                let worktree = fs::canonicalize(worktree).unwrap_or_else(|_| worktree.clone());
+                let relative = worktree.strip_prefix(canonical).ok()?;
+                Some((listing.path().join(relative), ProjectKind::Git))
+            }));
+            if self.config.submodules {
+                candidates.extend(
+                    git.submodules()
+                        .iter()
+                        .map(|submodule| listing.path().join(submodule.path()))
+                        .filter(|path| path.join(".git").exists())
+                        .map(|path| (path, ProjectKind::Git)),
+                );
+            }
+        }
+        if self.config.sub_projects {
+            let workspace = Workspace::read(listing);
+            scan.scanned_paths.extend(workspace.scanned_paths);
+            candidates.extend(
+                workspace
+                    .members
+                    .into_iter()
+                    .map(|member| (member.path, member.kind)),
+            );
+        }
+
+        let mut members: Vec<Project> = Vec::new();
+        for (path, kind) in candidates {
+            if members.iter().any(|member| member.info.path == path) {
+                continue;
+            }
+            let listing = match DirListing::read(&path) {
+                Ok(listing) => listing,
+                Err(err) => {
+                    scan.warnings.push(Warning::from_io(path, &err));
+                    continue;
+                }
+            };
+            let project = self
+                .detect_project(&listing, scan)
+                .unwrap_or_else(|| Project {
+                    info: Info::parse_dir_path(&path, &self.config.slug_rules),
+                    kinds: [kind].into_iter().collect(),
+                    links_to: None,
+                    canonical: None,
+                    worktree_of: None,
+                    members: Vec::new(),
+                    git: None,
+                    metadata: Arc::new(Metadata::read(&listing, &mut scan.scanned_paths)),
+                });
+            scan.scanned_paths.push(path);
+            members.push(project);
+        }
+
+        members.sort_by(|a: &Project, b: &Project| {
+            a.info
+                .name
+                .to_lowercase()
+                .cmp(&b.info.name.to_lowercase())
+                .then_with(|| a.info.path.cmp(&b.info.path))
+        });
+        members
+    }
+
+    /// Subdirectories in `listing` that are neither hidden nor ignored.
+    ///
+    /// Entries that cannot be read are skipped and added to `warnings`.
+    fn list_dir(
+        &self,
+        listing: &DirListing,
+        ignores: &IgnoreStack,
+        warnings: &mut Vec<Warning>,
+    ) -> Vec<Subdir> {
+        let mut subdirs = Vec::new();
+        for entry in &listing.entries {
+            let entry_path = listing.path.join(&entry.name);
+            let is_symlink = entry.file_type.is_some_and(|t| t.is_symlink());
+
+            // Only symlinks and entries of unknown type need another
+            // syscall to find out whether they are directories.
+            let id = if entry.file_type.is_some_and(|t| t.is_dir()) {
+                None
+            } else {
+                match fs::metadata(&entry_path) {
+                    Ok(metadata) if metadata.is_dir() => Some(dir_id(&entry_path, &metadata)),
+                    Ok(_) => continue,
+                    Err(err) => {
+                        if is_symlink {
+                            warnings.push(Warning::new(entry_path, WarningKind::BrokenSymlink));
+                        } else {
+                            warnings.push(Warning::from_io(entry_path, &err));
+                        }
+                        continue;
+                    }
+                }
+            };
+
+            let Some(str) = entry.name.to_str() else {
+                // Stored lossy, the warning has to be serializable for the index
+                let lossy_path = entry_path.to_string_lossy().into_owned().into();
+                warnings.push(Warning::new(lossy_path, WarningKind::InvalidName));
+                continue;
+            };
+
+            // A negated pattern can bring back a hidden directory.
+            let ignored = ignores
+                .is_ignored(&entry_path, true)
+                .unwrap_or(self.config.ignore_hidden && str.starts_with('.'));
+            if ignored {
+                continue;
+            }
+
+            subdirs.push(Subdir {
+                path: entry_path,
+                id,
+                is_symlink,
+            });
+        }
+
+        subdirs
+    }
+}
+
+/// Entries of a directory.
+///
+/// Every directory is read once while scanning, all detectors are
+/// evaluated against the same listing.
+pub struct DirListing {
+    path: PathBuf,
+    entries: Vec<ListingEntry>,
+    errors: Vec<std::io::Error>,
+}
+
+struct ListingEntry {
+    name: OsString,
+    file_type: Option<fs::FileType>,
+}
+
+impl DirListing {
+    /// Read the entries of `dir`.
+    ///
+    /// Only failing to open the directory is an error, entries that cannot
+    /// be read are skipped.
+    pub fn read(dir: &Path) -> std::io::Result<Self> {
+        let mut listing = Self::empty(dir.to_path_buf());
+        for entry in dir.read_dir()? {
+            match entry {
+                Ok(entry) => listing.entries.push(ListingEntry {
+                    name: entry.file_name(),
+                    file_type: entry.file_type().ok(),
+                }),
+                Err(err) => listing.errors.push(err),
+            }
+        }
+        Ok(listing)
+    }
+
+    fn empty(path: PathBuf) -> Self {
+        DirListing {
+            path,
+            entries: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Path of the listed directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Names of all entries, files as well as directories.
+    pub fn file_names(&self) -> impl Iterator<Item = &OsStr> {
+        self.entries.iter().map(|entry| entry.name.as_os_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.file_names().any(|file_name| file_name == name)
+    }
+
+    fn warnings(&self) -> Vec<Warning> {
+        self.errors
+            .iter()
+            .map(|err| Warning::from_io(self.path.clone(), err))
+            .collect()
+    }
+}
+
+/// Identifies a directory independent of the path it is reached by.
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
+
+#[cfg(unix)]
+fn dir_id(_path: &Path, metadata: &fs::Metadata) -> DirId {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.dev(), metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn dir_id(path: &Path, _metadata: &fs::Metadata) -> DirId {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Subdirectory found by [`Detector::list_dir`].
+struct Subdir {
+    path: PathBuf,
+    /// Only known up front for symlinks and entries of unknown type
+    id: Option<DirId>,
+    is_symlink: bool,
+}
+
+/// Position of a namespace directory during the walk.
+struct DirState {
+    /// Directories from the root down to this one
+    ancestors: Vec<DirId>,
+    /// Path with all symlinks resolved
+    canonical: PathBuf,
+    /// Whether the directory was reached through a symlink
+    linked: bool,
+    /// Number of directory levels below this one that are scanned
+    remaining_depth: Option<usize>,
+    /// Fully qualified slug of the namespace
+    slug: String,
+}
+
+struct ParsedNamespace {
+    items: Vec<NamespaceItem>,
+    /// Subdirectories were not scanned because of the maximum depth
+    truncated: bool,
+    scan: Scan,
+}
+
+struct ScannedProject {
+    path: PathBuf,
+    canonical: PathBuf,
+    linked: bool,
+}
+
+/// Data collected while walking the roots.
+#[derive(Default)]
+struct Scan {
+    scanned_paths: Vec<PathBuf>,
+    warnings: Vec<Warning>,
+    projects: Vec<ScannedProject>,
+}
+
+impl Scan {
+    fn append(&mut self, mut other: Scan) {
+        self.scanned_paths.append(&mut other.scanned_paths);
+        self.warnings.append(&mut other.warnings);
+        self.projects.append(&mut other.projects);
+    }
+}
+
+/// Map the paths of projects reached through a symlink to the path of the
+/// project they link to.
+///
+/// Projects reached without a symlink are always the canonical ones, if a
+/// directory is only reachable through symlinks the first link found is.
+fn find_links(projects: &[ScannedProject]) -> HashMap<PathBuf, PathBuf> {
+    let mut targets: HashMap<&Path, &Path> = projects
+        .iter()
+        .filter(|project| !project.linked)
+        .map(|project| (project.canonical.as_path(), project.path.as_path()))
+        .collect();
+
+    let mut links = HashMap::new();
+    for project in projects.iter().filter(|project| project.linked) {
+        match targets.entry(&project.canonical) {
+            Entry::Occupied(target) => {
+                links.insert(project.path.clone(), target.get().to_path_buf());
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(&project.path);
+            }
+        }
+    }
+    links
+}
+
+/// Replace the namespaces hidden by `rules` with their items, merging
+/// them into the surrounding namespace. `aggregated_slug` is the slug of
+/// the namespace containing `items` without any hidden namespaces.
+///
+/// All items are merged, so a namespace lifted out of a hidden one and a
+/// visible namespace with the same slug become one, whatever their order.
+fn hide_namespaces(
+    items: Vec<NamespaceItem>,
+    aggregated_slug: &str,
+    rules: &SlugRules,
+) -> Vec<NamespaceItem> {
+    let mut visible = Vec::new();
+    for item in items {
+        let NamespaceItem::Namespace(mut namespace) = item else {
+            merge_items(&mut visible, vec![item]);
+            continue;
+        };
+        let slug = qualify(
+            aggregated_slug,
+            &namespace.info.slug,
+            rules.namespace_separator(),
+        );
+        let items = hide_namespaces(std::mem::take(&mut namespace.items), &slug, rules);
+        if rules.is_hidden(&slug) {
+            merge_items(&mut visible, items);
+        } else {
+            namespace.items = items;
+            merge_items(&mut visible, vec![NamespaceItem::Namespace(namespace)]);
+        }
+    }
+    visible
+}
+
+/// Point git worktrees to the project of their main repository, looked up
+/// by canonical path.
+fn mark_worktrees(items: &mut [NamespaceItem], projects: &[ScannedProject]) {
+    fn mark_project(project: &mut Project, paths: &HashMap<&Path, &Path>) {
+        if let Some(main) = project.git.as_ref().and_then(GitInfo::main_repository) {
+            project.worktree_of = paths.get(main).map(|path| path.to_path_buf());
+        }
+        for member in project.members.iter_mut() {
+            mark_project(member, paths);
+        }
+    }
+
+    fn mark_items(items: &mut [NamespaceItem], paths: &HashMap<&Path, &Path>) {
+        for item in items {
+            match item {
+                NamespaceItem::Project(project) => mark_project(project, paths),
+                NamespaceItem::Namespace(namespace) => mark_items(&mut namespace.items, paths),
+            }
+        }
+    }
+
+    let paths: HashMap<&Path, &Path> = projects
+        .iter()
+        .filter(|project| !project.linked)
+        .map(|project| (project.canonical.as_path(), project.path.as_path()))
+        .collect();
+    mark_items(items, &paths);
+}
+
+fn mark_links(items: &mut [NamespaceItem], links: &HashMap<PathBuf, PathBuf>) {
+    if links.is_empty() {
+        return;
+    }
+    for item in items {
+        match item {
+            NamespaceItem::Project(project) => {
+                project.links_to = links.get(&project.info.path).cloned();
+            }
+            NamespaceItem::Namespace(namespace) => mark_links(&mut namespace.items, links),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    /// Root with a project, a symlink back to the root, a symlink to the
+    /// project and a symlink to a directory outside the root, which itself
+    /// holds a symlink to a third directory.
+    fn symlink_tree() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = |path: &str| {
+            fs::create_dir_all(tmp.path().join(path)).unwrap();
+            fs::write(tmp.path().join(path).join("Makefile"), "").unwrap();
+        };
+        dir("root/work/app");
+        dir("outside/tool");
+        dir("deeper/lib");
+
+        symlink(tmp.path().join("root"), tmp.path().join("root/work/loop")).unwrap();
+        symlink("work/app", tmp.path().join("root/shortcut")).unwrap();
+        symlink(tmp.path().join("outside"), tmp.path().join("root/ext")).unwrap();
+        symlink(tmp.path().join("deeper"), tmp.path().join("outside/nested")).unwrap();
+        tmp
+    }
+
+    fn detect(tmp: &tempfile::TempDir, policy: SymlinkPolicy) -> RootNamespace {
+        let config = DetectorConfig::default().symlinks(policy);
+        Detector::with_config(tmp.path().join("root"), config)
+            .detect()
+            .unwrap()
+    }
+
+    fn slugs(root: &RootNamespace) -> Vec<String> {
+        let mut slugs: Vec<_> = root
+            .build_project_slugs()
+            .iter()
+            .map(|slug_path| slug_path.slug().to_string())
+            .collect();
+        slugs.sort();
+        slugs
+    }
+
+    fn cycles(root: &RootNamespace) -> Vec<&Path> {
+        root.warnings()
+            .iter()
+            .filter(|warning| matches!(warning.kind(), WarningKind::SymlinkCycle))
+            .map(Warning::path)
+            .collect()
+    }
+
+    #[test]
+    fn follow_skips_cycles_only() {
+        let tmp = symlink_tree();
+        let root = detect(&tmp, SymlinkPolicy::Follow);
+
+        assert_eq!(
+            slugs(&root),
+            ["ext.nested.lib", "ext.tool", "shortcut", "work.app"]
+        );
+        assert_eq!(cycles(&root), [tmp.path().join("root/work/loop")]);
+    }
+
+    #[test]
+    fn follow_once_skips_symlinks_below_followed_ones() {
+        let tmp = symlink_tree();
+        let root = detect(&tmp, SymlinkPolicy::FollowOnce);
+
+        assert_eq!(slugs(&root), ["ext.tool", "shortcut", "work.app"]);
+        assert_eq!(cycles(&root), [tmp.path().join("root/work/loop")]);
+    }
+
+    #[test]
+    fn ignore_skips_all_symlinks() {
+        let tmp = symlink_tree();
+        let root = detect(&tmp, SymlinkPolicy::Ignore);
+
+        assert_eq!(slugs(&root), ["work.app"]);
+        assert!(cycles(&root).is_empty());
+    }
+
+    #[test]
+    fn marks_links_to_detected_projects() {
+        let tmp = symlink_tree();
+        let slug_paths = detect(&tmp, SymlinkPolicy::Follow).build_project_slugs();
+        let find = |slug: &str| {
+            slug_paths
+                .iter()
+                .find(|slug_path| slug_path.slug() == slug)
+                .unwrap()
+        };
+
+        let shortcut = find("shortcut");
+        assert!(shortcut.is_link());
+        assert_eq!(shortcut.links_to(), Some("work.app"));
+        let canonical = fs::canonicalize(tmp.path().join("root/work/app")).unwrap();
+        assert_eq!(shortcut.canonical_path(), Some(canonical.as_path()));
+
+        // Reached through a symlink, but there is no other copy to point to
+        let tool = find("ext.tool");
+        assert!(!tool.is_link());
+        assert_eq!(tool.links_to(), None);
+        assert!(tool.canonical_path().is_some());
+
+        let app = find("work.app");
+        assert!(!app.is_link());
+        assert_eq!(app.canonical_path(), None);
+    }
+}
@@ -0,0 +1,419 @@
+use super::{DirListing, ProjectDetector, ProjectKind};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Commit time of `HEAD` in the repository at `project`.
+///
+/// Asks `git`, as commits are usually stored compressed in pack files.
+/// The git directory is passed explicitly, so a directory that is merely
+/// inside a repository has no commit time.
+pub fn last_commit_time(project: &Path) -> Option<SystemTime> {
+    let output = Command::new("git")
+        .arg("--git-dir")
+        .arg(git_dir(project)?)
+        .args(["log", "-1", "--format=%ct", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let timestamp: u64 = std::str::from_utf8(&output.stdout)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+
+    Some(UNIX_EPOCH + Duration::from_secs(timestamp))
+}
+
+/// Detects git repositories: working directories with a `.git` directory
+/// or file and bare repositories.
+#[derive(Debug)]
+pub struct GitDetector;
+
+impl ProjectDetector for GitDetector {
+    fn name(&self) -> &str {
+        ProjectKind::Git.as_str()
+    }
+
+    fn kind(&self) -> ProjectKind {
+        ProjectKind::Git
+    }
+
+    fn detect(&self, listing: &DirListing) -> bool {
+        listing.contains(".git") || is_bare(listing)
+    }
+}
+
+/// Bare repositories have the layout of a `.git` directory.
+fn is_bare(listing: &DirListing) -> bool {
+    listing.contains("HEAD") && listing.contains("objects") && listing.contains("refs")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RepositoryKind {
+    Repository,
+    /// Linked worktree created with `git worktree add`
+    Worktree,
+    Bare,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Submodule {
+    name: String,
+    /// Path relative to the repository
+    path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+}
+
+impl Submodule {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+}
+
+/// Layout of a git repository and the repositories linked to it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GitInfo {
+    kind: RepositoryKind,
+    /// Repository a worktree belongs to, either the working directory of
+    /// the main worktree or the bare repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    main_repository: Option<PathBuf>,
+    /// Linked worktrees of the repository
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    worktrees: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    submodules: Vec<Submodule>,
+}
+
+impl GitInfo {
+    /// Inspect the repository in the directory described by `listing`,
+    /// `None` if it is not a repository.
+    ///
+    /// Files that were read are added to `scanned_paths`.
+    pub(crate) fn read(listing: &DirListing, scanned_paths: &mut Vec<PathBuf>) -> Option<Self> {
+        let dir = listing.path();
+
+        let (kind, git_dir, main_repository) = if listing.contains(".git") {
+            let dot_git = dir.join(".git");
+            if dot_git.is_dir() {
+                (RepositoryKind::Repository, dot_git, None)
+            } else {
+                // `gitdir: <path>` of worktrees, submodules and separated
+                // git directories
+                let content = fs::read_to_string(&dot_git).ok()?;
+                let git_dir = resolve(dir, content.strip_prefix("gitdir:")?.trim());
+                match fs::read_to_string(git_dir.join("commondir")) {
+                    Ok(common_dir) => {
+                        let common_dir = resolve(&git_dir, common_dir.trim());
+                        let main = match common_dir.file_name() {
+                            Some(name) if name == ".git" => {
+                                common_dir.parent().map(Path::to_path_buf)
+                            }
+                            _ => Some(common_dir.clone()),
+                        };
+                        (RepositoryKind::Worktree, git_dir, main)
+                    }
+                    Err(_) => (RepositoryKind::Repository, git_dir, None),
+                }
+            }
+        } else if is_bare(listing) {
+            (RepositoryKind::Bare, dir.to_path_buf(), None)
+        } else {
+            return None;
+        };
+
+        let worktrees = match kind {
+            RepositoryKind::Worktree => Vec::new(),
+            _ => read_worktrees(&git_dir, scanned_paths),
+        };
+        let submodules = match kind {
+            RepositoryKind::Bare => Vec::new(),
+            _ => read_submodules(dir, scanned_paths),
+        };
+
+        Some(GitInfo {
+            kind,
+            main_repository,
+            worktrees,
+            submodules,
+        })
+    }
+
+    pub fn kind(&self) -> RepositoryKind {
+        self.kind
+    }
+
+    pub fn main_repository(&self) -> Option<&Path> {
+        self.main_repository.as_deref()
+    }
+
+    pub fn worktrees(&self) -> &[PathBuf] {
+        &self.worktrees
+    }
+
+    pub fn submodules(&self) -> &[Submodule] {
+        &self.submodules
+    }
+}
+
+/// Git directory of the repository at `dir`, following the `gitdir:`
+/// link of worktrees and submodules.
+fn git_dir(dir: &Path) -> Option<PathBuf> {
+    let dot_git = dir.join(".git");
+    if dot_git.is_dir() {
+        Some(dot_git)
+    } else if dot_git.is_file() {
+        let content = fs::read_to_string(&dot_git).ok()?;
+        Some(resolve(dir, content.strip_prefix("gitdir:")?.trim()))
+    } else if dir.join("HEAD").is_file() {
+        Some(dir.to_path_buf())
+    } else {
+        None
+    }
+}
+
+/// `path` relative to `base`, with `..` resolved if the path exists.
+fn resolve(base: &Path, path: &str) -> PathBuf {
+    let path = base.join(path);
+    fs::canonicalize(&path).unwrap_or(path)
+}
+
+/// Working directories of the worktrees registered in `git_dir`.
+fn read_worktrees(git_dir: &Path, scanned_paths: &mut Vec<PathBuf>) -> Vec<PathBuf> {
+    let worktrees_dir = git_dir.join("worktrees");
+    let Ok(read_dir) = worktrees_dir.read_dir() else {
+        return Vec::new();
+    };
+    scanned_paths.push(worktrees_dir);
+
+    let mut worktrees: Vec<PathBuf> = read_dir
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path().join("gitdir")).ok())
+        .filter_map(|gitdir| Path::new(gitdir.trim()).parent().map(Path::to_path_buf))
+        .filter(|worktree| worktree.is_dir())
+        .collect();
+    worktrees.sort();
+    worktrees
+}
+
+/// Submodules declared in `.gitmodules`.
+fn read_submodules(dir: &Path, scanned_paths: &mut Vec<PathBuf>) -> Vec<Submodule> {
+    let gitmodules = dir.join(".gitmodules");
+    let Ok(content) = fs::read_to_string(&gitmodules) else {
+        return Vec::new();
+    };
+    scanned_paths.push(gitmodules);
+
+    parse_gitmodules(&content)
+}
+
+/// Submodules of the `[submodule "name"]` sections of a `.gitmodules`
+/// file, sections without a path are skipped.
+fn parse_gitmodules(content: &str) -> Vec<Submodule> {
+    let mut submodules = Vec::new();
+    let mut current: Option<(String, Option<PathBuf>, Option<String>)> = None;
+    let mut finish = |current: Option<(String, Option<PathBuf>, Option<String>)>| {
+        if let Some((name, Some(path), url)) = current {
+            submodules.push(Submodule { name, path, url });
+        }
+    };
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[') {
+            finish(current.take());
+            current = section
+                .strip_prefix("submodule")
+                .and_then(|name| name.trim().strip_suffix(']'))
+                .map(|name| (name.trim().trim_matches('"').to_string(), None, None));
+        } else if let (Some((_, path, url)), Some((key, value))) =
+            (current.as_mut(), line.split_once('='))
+        {
+            match key.trim() {
+                "path" => *path = Some(value.trim().into()),
+                "url" => *url = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+    finish(current);
+
+    submodules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn read(dir: &Path) -> Option<GitInfo> {
+        GitInfo::read(&DirListing::read(dir).unwrap(), &mut Vec::new())
+    }
+
+    #[test]
+    fn parses_gitmodules() {
+        let content = r#"
+# comment
+[submodule "vendor/lib"]
+    path = vendor/lib
+    url = https://example.com/lib.git
+[submodule "no-path"]
+    url = https://example.com/other.git
+[core]
+    path = not/a/submodule
+[submodule docs]
+    ; comment
+    path=docs
+"#;
+        let submodules = parse_gitmodules(content);
+        let fields: Vec<_> = submodules
+            .iter()
+            .map(|submodule| (submodule.name(), submodule.path(), submodule.url()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                (
+                    "vendor/lib",
+                    Path::new("vendor/lib"),
+                    Some("https://example.com/lib.git")
+                ),
+                ("docs", Path::new("docs"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_bare_repositories() {
+        let tmp = tempfile::tempdir().unwrap();
+        let bare = tmp.path().join("repo.git");
+        write(&bare.join("HEAD"), "ref: refs/heads/main\n");
+        fs::create_dir(bare.join("objects")).unwrap();
+        assert!(!is_bare(&DirListing::read(&bare).unwrap()));
+
+        fs::create_dir(bare.join("refs")).unwrap();
+        assert!(is_bare(&DirListing::read(&bare).unwrap()));
+        let info = read(&bare).unwrap();
+        assert_eq!(info.kind(), RepositoryKind::Bare);
+        assert!(info.submodules().is_empty());
+
+        assert!(read(tmp.path()).is_none());
+    }
+
+    #[test]
+    fn links_worktrees_and_main_repository() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main = tmp.path().join("main");
+        let feature = tmp.path().join("feature");
+        let admin = main.join(".git/worktrees/feature");
+        write(&main.join(".git/HEAD"), "ref: refs/heads/main\n");
+        write(
+            &admin.join("gitdir"),
+            &format!("{}\n", feature.join(".git").display()),
+        );
+        write(&admin.join("commondir"), "../..\n");
+        write(
+            &feature.join(".git"),
+            &format!("gitdir: {}\n", admin.display()),
+        );
+        let main = fs::canonicalize(main).unwrap();
+
+        let info = read(&main).unwrap();
+        assert_eq!(info.kind(), RepositoryKind::Repository);
+        assert_eq!(info.worktrees(), std::slice::from_ref(&feature));
+
+        let info = read(&feature).unwrap();
+        assert_eq!(info.kind(), RepositoryKind::Worktree);
+        assert_eq!(info.main_repository(), Some(main.as_path()));
+        assert!(info.worktrees().is_empty());
+    }
+
+    #[test]
+    fn worktrees_of_bare_repositories_point_to_it() {
+        let tmp = tempfile::tempdir().unwrap();
+        let bare = tmp.path().join("repo.git");
+        let feature = tmp.path().join("feature");
+        write(&bare.join("worktrees/feature/commondir"), "../..\n");
+        write(
+            &feature.join(".git"),
+            "gitdir: ../repo.git/worktrees/feature\n",
+        );
+
+        let info = read(&feature).unwrap();
+        assert_eq!(info.kind(), RepositoryKind::Worktree);
+        let bare = fs::canonicalize(bare).unwrap();
+        assert_eq!(info.main_repository(), Some(bare.as_path()));
+    }
+
+    #[test]
+    fn separated_git_dirs_are_repositories() {
+        let tmp = tempfile::tempdir().unwrap();
+        let sub = tmp.path().join("main/vendor/lib");
+        write(
+            &tmp.path().join("main/.git/modules/lib/HEAD"),
+            "ref: refs/heads/main\n",
+        );
+        write(&sub.join(".git"), "gitdir: ../../.git/modules/lib\n");
+
+        let info = read(&sub).unwrap();
+        assert_eq!(info.kind(), RepositoryKind::Repository);
+        assert_eq!(info.main_repository(), None);
+    }
+
+    #[test]
+    fn last_commit_time_is_the_commit_time() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("repo");
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(args)
+                .env("GIT_AUTHOR_NAME", "test")
+                .env("GIT_AUTHOR_EMAIL", "test@example.com")
+                .env("GIT_COMMITTER_NAME", "test")
+                .env("GIT_COMMITTER_EMAIL", "test@example.com")
+                .env("GIT_COMMITTER_DATE", "@1700000000 +0000")
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        fs::create_dir(&repo).unwrap();
+        git(&["init", "-q"]);
+        assert_eq!(last_commit_time(&repo), None);
+
+        git(&["commit", "-q", "--allow-empty", "-m", "initial"]);
+        assert_eq!(
+            last_commit_time(&repo),
+            Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+        // Only the repository itself has a commit time
+        fs::create_dir(repo.join("sub")).unwrap();
+        assert_eq!(last_commit_time(&repo.join("sub")), None);
+    }
+}
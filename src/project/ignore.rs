@@ -0,0 +1,207 @@
+use super::{Warning, WarningKind};
+use crate::error::Error;
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    Match,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Name of the per-namespace ignore files.
+pub const IGNORE_FILE_NAME: &str = ".pmignore";
+
+/// Check that `pattern` is a valid gitignore pattern.
+pub(crate) fn validate_pattern(pattern: &str) -> Result<(), Error> {
+    GitignoreBuilder::new("/")
+        .add_line(None, pattern)
+        .map(|_| ())
+        .map_err(|err| Error::InvalidIgnorePattern {
+            pattern: pattern.to_string(),
+            reason: err.to_string(),
+        })
+}
+
+/// Gitignore style rules in effect for a directory.
+///
+/// Holds the configured patterns, anchored at the project root, and the
+/// rules of every `.pmignore` file between the root and the directory.
+/// Rules of deeper directories take precedence.
+#[derive(Clone, Default)]
+pub(crate) struct IgnoreStack {
+    matchers: Vec<Arc<Gitignore>>,
+}
+
+impl IgnoreStack {
+    /// Rules for `root` from the configured `patterns`.
+    pub(crate) fn new(root: &Path, patterns: &[String]) -> Self {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in patterns {
+            // Patterns are validated when they are added to the config.
+            let _ = builder.add_line(None, pattern);
+        }
+
+        let mut stack = IgnoreStack::default();
+        if let Ok(matcher) = builder.build() {
+            stack.push(matcher);
+        }
+        stack
+    }
+
+    /// Rules for the directory `dir`, which is a child of the directory
+    /// these rules belong to.
+    ///
+    /// Returns the path of the ignore file of `dir` if it has one. Lines of
+    /// the file that cannot be used are skipped and added to `warnings`.
+    pub(crate) fn enter(&self, dir: &Path, warnings: &mut Vec<Warning>) -> (Self, Option<PathBuf>) {
+        let ignore_file = dir.join(IGNORE_FILE_NAME);
+        if !ignore_file.is_file() {
+            return (self.clone(), None);
+        }
+
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut errors = match builder.add(&ignore_file) {
+            Some(ignore::Error::Partial(errors)) => errors,
+            error => error.into_iter().collect(),
+        };
+
+        let mut stack = self.clone();
+        match builder.build() {
+            Ok(matcher) => stack.push(matcher),
+            Err(err) => errors.push(err),
+        }
+        warnings.extend(errors.iter().map(|err| {
+            Warning::new(
+                ignore_file.clone(),
+                WarningKind::InvalidIgnoreFile(describe(err)),
+            )
+        }));
+        (stack, Some(ignore_file))
+    }
+
+    fn push(&mut self, matcher: Gitignore) {
+        if !matcher.is_empty() {
+            self.matchers.push(Arc::new(matcher));
+        }
+    }
+
+    /// Decide whether `path` is ignored (`Some(true)`), explicitly
+    /// whitelisted by a negated pattern (`Some(false)`) or not mentioned by
+    /// any rule (`None`).
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        self.matchers
+            .iter()
+            .rev()
+            .find_map(|matcher| match matcher.matched(path, is_dir) {
+                Match::None => None,
+                Match::Ignore(_) => Some(true),
+                Match::Whitelist(_) => Some(false),
+            })
+    }
+}
+
+/// Message of `err` without the path of the ignore file, which the warning
+/// already names.
+fn describe(err: &ignore::Error) -> String {
+    match err {
+        ignore::Error::WithPath { err, .. } => describe(err),
+        ignore::Error::WithLineNumber { line, err } => format!("line {}: {}", line, describe(err)),
+        err => err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn ignore_file(dir: &Path, lines: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(IGNORE_FILE_NAME), lines).unwrap();
+    }
+
+    #[test]
+    fn deeper_rules_take_precedence() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        ignore_file(&root.join("work"), "!build\n");
+
+        let mut warnings = Vec::new();
+        let stack = IgnoreStack::new(root, &["build".to_string()]);
+        assert_eq!(stack.is_ignored(&root.join("build"), true), Some(true));
+        assert_eq!(stack.is_ignored(&root.join("src"), true), None);
+
+        let (work, file) = stack.enter(&root.join("work"), &mut warnings);
+        assert_eq!(file, Some(root.join("work").join(IGNORE_FILE_NAME)));
+        assert_eq!(work.is_ignored(&root.join("work/build"), true), Some(false));
+        // The parent's rules are unchanged
+        assert_eq!(stack.is_ignored(&root.join("oss/build"), true), Some(true));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn later_lines_negate_earlier_ones() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        ignore_file(root, "/*\n!/keep\n");
+
+        let mut warnings = Vec::new();
+        let (stack, _) = IgnoreStack::new(root, &[]).enter(root, &mut warnings);
+        assert_eq!(stack.is_ignored(&root.join("drop"), true), Some(true));
+        assert_eq!(stack.is_ignored(&root.join("keep"), true), Some(false));
+        // Anchored patterns only match at the level of the ignore file
+        assert_eq!(stack.is_ignored(&root.join("keep/drop"), true), None);
+    }
+
+    #[test]
+    fn dir_only_patterns_skip_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+
+        let stack = IgnoreStack::new(root, &["target/".to_string()]);
+        assert_eq!(stack.is_ignored(&root.join("target"), true), Some(true));
+        assert_eq!(stack.is_ignored(&root.join("target"), false), None);
+    }
+
+    #[test]
+    fn reports_invalid_lines_and_keeps_the_others() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        ignore_file(root, "build\n[z-a]\n");
+
+        let mut warnings = Vec::new();
+        let (stack, _) = IgnoreStack::new(root, &[]).enter(root, &mut warnings);
+        assert_eq!(stack.is_ignored(&root.join("build"), true), Some(true));
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].path(), root.join(IGNORE_FILE_NAME));
+        match warnings[0].kind() {
+            WarningKind::InvalidIgnoreFile(reason) => {
+                assert!(reason.starts_with("line 2: "), "{}", reason)
+            }
+            kind => panic!("unexpected warning {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn without_ignore_file_keeps_the_rules() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+
+        let mut warnings = Vec::new();
+        let stack = IgnoreStack::new(root, &["build".to_string()]);
+        let (sub, file) = stack.enter(&root.join("sub"), &mut warnings);
+        assert_eq!(file, None);
+        assert_eq!(sub.is_ignored(&root.join("sub/build"), true), Some(true));
+    }
+
+    #[test]
+    fn validates_patterns() {
+        assert!(validate_pattern("!/build/").is_ok());
+        assert!(matches!(
+            validate_pattern("[z-a]"),
+            Err(Error::InvalidIgnorePattern { .. })
+        ));
+    }
+}
@@ -0,0 +1,313 @@
+use super::{project_file::ProjectFile, DirListing};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// File names checked for a README, in order.
+const README_NAMES: &[&str] = &["README.md", "readme.md", "Readme.md", "README"];
+
+/// Information about a project read from its manifests and its
+/// project file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Metadata {
+    /// Package name from `Cargo.toml` or `package.json`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    /// Module path from `go.mod`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    module: Option<String>,
+    /// First heading of the README
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    /// Additional slugs from the project file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    generator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    publisher: Option<String>,
+    /// Directories relative to the project, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    bookmarks: BTreeMap<String, PathBuf>,
+}
+
+impl Metadata {
+    /// Read the manifests in the directory described by `listing`.
+    ///
+    /// Earlier manifests take precedence: `Cargo.toml`, `package.json`,
+    /// `go.mod`, `flake.nix` and finally the README. Files that were read
+    /// are added to `scanned_paths`.
+    pub(crate) fn read(listing: &DirListing, scanned_paths: &mut Vec<PathBuf>) -> Self {
+        let mut metadata = Metadata::default();
+        let mut read = |name: &str| -> Option<String> {
+            if !listing.contains(name) {
+                return None;
+            }
+            let path = listing.path().join(name);
+            let content = fs::read_to_string(&path).ok()?;
+            scanned_paths.push(path);
+            Some(content)
+        };
+
+        if let Some(content) = read("Cargo.toml") {
+            metadata.read_cargo(&content);
+        }
+        if let Some(content) = read("package.json") {
+            metadata.read_npm(&content);
+        }
+        if let Some(content) = read("go.mod") {
+            metadata.module = content
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("module "))
+                .map(|module| module.trim().trim_matches('"').to_string());
+        }
+        if let Some(content) = read("flake.nix") {
+            metadata.description = metadata
+                .description
+                .take()
+                .or_else(|| nix_description(&content));
+        }
+        if let Some(content) = README_NAMES.iter().find_map(|name| read(name)) {
+            metadata.title = readme_title(&content);
+        }
+
+        metadata
+    }
+
+    /// Add the settings of a project file, its description takes
+    /// precedence over the manifests.
+    pub(crate) fn with_project_file(mut self, file: ProjectFile) -> Self {
+        self.description = file.description.or(self.description);
+        self.aliases = file.aliases;
+        self.tags = file.tags;
+        self.generator = file.generator;
+        self.publisher = file.publisher;
+        self.bookmarks = file.bookmarks;
+        self
+    }
+
+    fn read_cargo(&mut self, content: &str) {
+        // Fields inherited from the workspace are tables, not strings.
+        #[derive(Deserialize)]
+        struct Manifest {
+            package: Option<Package>,
+        }
+        #[derive(Deserialize)]
+        struct Package {
+            name: Option<toml::Value>,
+            version: Option<toml::Value>,
+            description: Option<toml::Value>,
+        }
+
+        let Some(package) = toml::from_str::<Manifest>(content)
+            .ok()
+            .and_then(|manifest| manifest.package)
+        else {
+            return;
+        };
+        let string = |value: Option<toml::Value>| match value {
+            Some(toml::Value::String(value)) => Some(value),
+            _ => None,
+        };
+
+        self.name = self.name.take().or(string(package.name));
+        self.version = self.version.take().or(string(package.version));
+        self.description = self.description.take().or(string(package.description));
+    }
+
+    fn read_npm(&mut self, content: &str) {
+        #[derive(Deserialize)]
+        struct Manifest {
+            name: Option<serde_json::Value>,
+            version: Option<serde_json::Value>,
+            description: Option<serde_json::Value>,
+        }
+
+        let Ok(manifest) = serde_json::from_str::<Manifest>(content) else {
+            return;
+        };
+        let string = |value: Option<serde_json::Value>| match value {
+            Some(serde_json::Value::String(value)) => Some(value),
+            _ => None,
+        };
+
+        self.name = self.name.take().or(string(manifest.name));
+        self.version = self.version.take().or(string(manifest.version));
+        self.description = self.description.take().or(string(manifest.description));
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn module(&self) -> Option<&str> {
+        self.module.as_deref()
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Additional slugs the project is matched by exactly.
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    pub fn generator(&self) -> Option<&str> {
+        self.generator.as_deref()
+    }
+
+    pub fn publisher(&self) -> Option<&str> {
+        self.publisher.as_deref()
+    }
+
+    pub fn bookmarks(&self) -> &BTreeMap<String, PathBuf> {
+        &self.bookmarks
+    }
+
+    /// Directory of the bookmark `name`, relative to the project.
+    pub fn bookmark(&self, name: &str) -> Option<&Path> {
+        self.bookmarks.get(name).map(PathBuf::as_path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.version.is_none()
+            && self.description.is_none()
+            && self.module.is_none()
+            && self.title.is_none()
+            && self.aliases.is_empty()
+            && self.tags.is_empty()
+            && self.generator.is_none()
+            && self.publisher.is_none()
+            && self.bookmarks.is_empty()
+    }
+}
+
+/// Value of the top level `description = "...";` attribute of a flake.
+fn nix_description(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("description")?.trim_start();
+        let value = value.strip_prefix('=')?.trim();
+        let value = value.strip_prefix('"')?;
+        let end = value.find('"')?;
+        Some(value[..end].to_string())
+    })
+}
+
+/// First ATX (`# Title`) or setext (`Title` underlined with `===`)
+/// heading of a markdown document.
+///
+/// Fenced code blocks and a leading YAML front matter are skipped, so
+/// shell comments and metadata are not mistaken for headings.
+fn readme_title(content: &str) -> Option<String> {
+    let mut lines: Vec<&str> = content.lines().map(str::trim).collect();
+    if lines.first() == Some(&"---") {
+        let end = lines.iter().skip(1).position(|line| *line == "---")?;
+        lines.drain(..end + 2);
+    }
+
+    let mut fence: Option<&str> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(marker) = fence {
+            if line.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| line.starts_with(m)) {
+            fence = Some(marker);
+            continue;
+        }
+
+        let level = line.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&level) {
+            let heading = &line[level..];
+            if heading.is_empty() || heading.starts_with(char::is_whitespace) {
+                let heading = heading.trim().trim_end_matches('#').trim_end();
+                if !heading.is_empty() {
+                    return Some(heading.to_string());
+                }
+                continue;
+            }
+        }
+
+        let Some(underline) = lines.get(i + 1) else {
+            break;
+        };
+        let is_setext = !line.is_empty()
+            && !underline.is_empty()
+            && (underline.chars().all(|c| c == '=') || underline.chars().all(|c| c == '-'));
+        if is_setext {
+            return Some(line.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readme_title_atx() {
+        assert_eq!(
+            readme_title("# Project\n\ntext").as_deref(),
+            Some("Project")
+        );
+        assert_eq!(readme_title("\n## Sub ##\n").as_deref(), Some("Sub"));
+        assert_eq!(readme_title("#\n# Title").as_deref(), Some("Title"));
+        assert_eq!(readme_title("#hashtag\n# Title").as_deref(), Some("Title"));
+        assert_eq!(readme_title("####### seven"), None);
+    }
+
+    #[test]
+    fn readme_title_setext() {
+        assert_eq!(
+            readme_title("Project\n=======\n").as_deref(),
+            Some("Project")
+        );
+        assert_eq!(readme_title("Project\n---").as_deref(), Some("Project"));
+        assert_eq!(readme_title("text\n\n---\n"), None);
+    }
+
+    #[test]
+    fn readme_title_skips_code_and_front_matter() {
+        let readme = "```sh\n# install\nmake\n```\n# Title";
+        assert_eq!(readme_title(readme).as_deref(), Some("Title"));
+        let readme = "---\nlayout: page\n---\nTitle\n===";
+        assert_eq!(readme_title(readme).as_deref(), Some("Title"));
+        assert_eq!(readme_title("---\nlayout: page\n"), None);
+    }
+
+    #[test]
+    fn readme_title_without_heading() {
+        assert_eq!(readme_title(""), None);
+        assert_eq!(readme_title("Just some text.\nMore text."), None);
+    }
+}
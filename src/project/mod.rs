@@ -0,0 +1,1269 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+mod detector;
+pub mod git;
+mod ignore;
+mod metadata;
+mod project_file;
+mod slug;
+mod workspace;
+
+pub use detector::{
+    Detector, DetectorConfig, DetectorRegistry, DirListing, MarkerDetector, ProjectDetector,
+    ProjectRoot, SymlinkPolicy,
+};
+pub use git::{GitDetector, GitInfo, RepositoryKind, Submodule};
+pub use ignore::IGNORE_FILE_NAME;
+pub use metadata::Metadata;
+pub use project_file::{is_inside, PROJECT_FILE_NAME};
+pub use slug::{SlugRules, MEMBER_SEPARATOR, PATH_SEPARATOR};
+
+/// Ecosystem or build system that identified a directory as project.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ProjectKind {
+    Git,
+    Zig,
+    Cargo,
+    Meson,
+    Make,
+    CMake,
+    Node,
+    Nix,
+    Go,
+    /// Directory containing a project file
+    ProjectFile,
+    /// Kind of a detector that is not built in
+    Custom(Arc<str>),
+}
+
+impl ProjectKind {
+    const BUILTIN: &'static [ProjectKind] = &[
+        ProjectKind::Git,
+        ProjectKind::Zig,
+        ProjectKind::Cargo,
+        ProjectKind::Meson,
+        ProjectKind::Make,
+        ProjectKind::CMake,
+        ProjectKind::Node,
+        ProjectKind::Nix,
+        ProjectKind::Go,
+        ProjectKind::ProjectFile,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ProjectKind::Git => "git",
+            ProjectKind::Zig => "zig",
+            ProjectKind::Cargo => "cargo",
+            ProjectKind::Meson => "meson",
+            ProjectKind::Make => "make",
+            ProjectKind::CMake => "cmake",
+            ProjectKind::Node => "node",
+            ProjectKind::Nix => "nix",
+            ProjectKind::Go => "go",
+            ProjectKind::ProjectFile => "project-file",
+            ProjectKind::Custom(kind) => kind,
+        }
+    }
+}
+
+impl From<&str> for ProjectKind {
+    fn from(kind: &str) -> Self {
+        ProjectKind::BUILTIN
+            .iter()
+            .find(|builtin| builtin.as_str() == kind)
+            .cloned()
+            .unwrap_or_else(|| ProjectKind::Custom(kind.into()))
+    }
+}
+
+impl std::fmt::Display for ProjectKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for ProjectKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ProjectKind {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let kind = String::deserialize(deserializer)?;
+        Ok(kind.as_str().into())
+    }
+}
+
+/// All kinds a project was detected as, e.g. git, cargo and nix at once.
+///
+/// Kinds are ordered by the priority of the detectors that found them,
+/// the first one is the primary kind.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProjectKinds(Vec<ProjectKind>);
+
+impl ProjectKinds {
+    /// Add `kind` unless it is already contained.
+    pub fn insert(&mut self, kind: ProjectKind) {
+        if !self.contains(&kind) {
+            self.0.push(kind);
+        }
+    }
+
+    pub fn contains(&self, kind: &ProjectKind) -> bool {
+        self.0.contains(kind)
+    }
+
+    pub fn primary(&self) -> Option<&ProjectKind> {
+        self.0.first()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ProjectKind> {
+        self.0.iter()
+    }
+}
+
+impl FromIterator<ProjectKind> for ProjectKinds {
+    fn from_iter<I: IntoIterator<Item = ProjectKind>>(iter: I) -> Self {
+        let mut kinds = ProjectKinds::default();
+        for kind in iter {
+            kinds.insert(kind);
+        }
+        kinds
+    }
+}
+
+impl std::fmt::Display for ProjectKinds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kinds: Vec<&str> = self.0.iter().map(ProjectKind::as_str).collect();
+        write!(f, "{}", kinds.join(","))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Project {
+    info: Info,
+    kinds: ProjectKinds,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    links_to: Option<PathBuf>,
+    /// Path with symlinks resolved, only stored if the project was reached
+    /// through a symlink
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    canonical: Option<PathBuf>,
+    /// Path of the project of the main repository of a git worktree, if
+    /// it was detected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    worktree_of: Option<PathBuf>,
+    /// Workspace members inside the project directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    members: Vec<Project>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    git: Option<GitInfo>,
+    /// Shared with the slug paths of the project
+    #[serde(default, skip_serializing_if = "no_metadata")]
+    metadata: Arc<Metadata>,
+}
+
+fn no_metadata(metadata: &Arc<Metadata>) -> bool {
+    metadata.is_empty()
+}
+
+impl Project {
+    pub fn new(name: Arc<str>, slug: Arc<str>, path: PathBuf, kinds: ProjectKinds) -> Self {
+        Project {
+            info: Info { name, slug, path },
+            kinds,
+            links_to: None,
+            canonical: None,
+            worktree_of: None,
+            members: Vec::new(),
+            git: None,
+            metadata: Arc::default(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    pub fn slug(&self) -> &str {
+        &self.info.slug
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.info.path
+    }
+
+    pub fn kinds(&self) -> &ProjectKinds {
+        &self.kinds
+    }
+
+    /// Path of the project this one is a symlink to, if the project was
+    /// reached through a symlink to a directory that is also detected
+    /// elsewhere.
+    pub fn links_to(&self) -> Option<&Path> {
+        self.links_to.as_deref()
+    }
+
+    /// Sub-projects listed in the workspace manifests of the project, only
+    /// detected if sub-project discovery is enabled.
+    pub fn members(&self) -> &[Project] {
+        &self.members
+    }
+
+    /// Repository layout, for projects detected as git repositories.
+    pub fn git(&self) -> Option<&GitInfo> {
+        self.git.as_ref()
+    }
+
+    /// Package information read from the manifests of the project.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+}
+
+impl std::fmt::Display for Project {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Project '{}' ({}): {}",
+            self.info.name,
+            self.info.slug,
+            self.info.path.display()
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Info {
+    name: Arc<str>,
+    slug: Arc<str>,
+    path: PathBuf,
+}
+
+impl Info {
+    /// Info named after the last component of `dir`, with a slug derived
+    /// by `rules`.
+    ///
+    /// Directories with names that are not valid UTF-8 are skipped during
+    /// detection, so the lossy conversion only matters for paths like `/`.
+    fn parse_dir_path(dir: &Path, rules: &SlugRules) -> Info {
+        let name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_else(|| dir.to_string_lossy());
+        Info {
+            name: name.as_ref().into(),
+            slug: rules.slugify(&name).into(),
+            path: dir.to_path_buf(),
+        }
+    }
+}
+
+/// Fully qualified slug of `slug` inside the namespace `aggregated_slug`.
+fn qualify(aggregated_slug: &str, slug: &str, separator: char) -> String {
+    if aggregated_slug.is_empty() {
+        slug.to_string()
+    } else {
+        format!("{}{}{}", aggregated_slug, separator, slug)
+    }
+}
+
+/// Flattened view of a project together with its fully qualified slug.
+#[derive(Clone, Serialize)]
+pub struct SlugPath {
+    slug: Arc<str>,
+    name: Arc<str>,
+    path: Arc<Path>,
+    kinds: ProjectKinds,
+    /// Slugs of the namespaces containing the project, outermost first
+    namespaces: Vec<Arc<str>>,
+    /// Slug of the project this one is a symlink to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    links_to: Option<Arc<str>>,
+    /// Slug of the project whose workspace member this one is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<Arc<str>>,
+    /// Slug of the main repository of a git worktree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    worktree_of: Option<Arc<str>>,
+    /// Additional names the project is matched by exactly
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<Arc<str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git: Option<GitInfo>,
+    #[serde(skip_serializing_if = "no_metadata")]
+    metadata: Arc<Metadata>,
+    #[serde(skip)]
+    link_path: Option<Arc<Path>>,
+    #[serde(skip)]
+    worktree_path: Option<Arc<Path>>,
+    #[serde(skip)]
+    canonical: Option<Arc<Path>>,
+    /// Namespace separator the slug was built with
+    #[serde(skip)]
+    separator: char,
+}
+
+impl std::fmt::Display for SlugPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.slug)
+    }
+}
+
+impl SlugPath {
+    fn new(namespaces: Vec<Arc<str>>, project: &Project, separator: char) -> Self {
+        let slug = if namespaces.is_empty() {
+            project.info.slug.clone()
+        } else {
+            let namespace = namespaces.join(separator.encode_utf8(&mut [0; 4]));
+            qualify(&namespace, &project.info.slug, separator).into()
+        };
+
+        SlugPath {
+            slug,
+            name: project.info.name.clone(),
+            path: Arc::from(project.info.path.as_path()),
+            kinds: project.kinds.clone(),
+            namespaces,
+            links_to: None,
+            parent: None,
+            worktree_of: None,
+            aliases: project_aliases(project),
+            git: project.git.clone(),
+            metadata: project.metadata.clone(),
+            link_path: project.links_to.as_deref().map(Arc::from),
+            worktree_path: project.worktree_of.as_deref().map(Arc::from),
+            canonical: project.canonical.as_deref().map(Arc::from),
+            separator,
+        }
+    }
+
+    /// Slug path of the workspace `member` of the project `self`, its slug
+    /// is `<project slug>:<member slug>`.
+    fn member(&self, member: &Project) -> Self {
+        SlugPath {
+            slug: format!("{}{}{}", self.slug, MEMBER_SEPARATOR, member.info.slug).into(),
+            name: member.info.name.clone(),
+            path: Arc::from(member.info.path.as_path()),
+            kinds: member.kinds.clone(),
+            namespaces: self.namespaces.clone(),
+            links_to: None,
+            parent: Some(self.slug.clone()),
+            worktree_of: None,
+            aliases: project_aliases(member),
+            git: member.git.clone(),
+            metadata: member.metadata.clone(),
+            link_path: None,
+            worktree_path: member.worktree_of.as_deref().map(Arc::from),
+            canonical: self.canonical.as_ref().and_then(|canonical| {
+                let relative = member.info.path.strip_prefix(&self.path).ok()?;
+                Some(Arc::from(canonical.join(relative)))
+            }),
+            separator: self.separator,
+        }
+    }
+
+    /// Slug paths of `project` and its workspace members.
+    fn with_members(namespaces: Vec<Arc<str>>, project: &Project, separator: char) -> Vec<Self> {
+        let slug_path = SlugPath::new(namespaces, project, separator);
+        // Members of a link are links as well, they are listed below
+        // the canonical project only.
+        let members: Vec<SlugPath> = if project.links_to.is_none() {
+            project
+                .members
+                .iter()
+                .map(|member| slug_path.member(member))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut slug_paths = vec![slug_path];
+        slug_paths.extend(members);
+        slug_paths
+    }
+
+    pub fn slug(&self) -> &str {
+        &self.slug
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn kinds(&self) -> &ProjectKinds {
+        &self.kinds
+    }
+
+    pub fn namespaces(&self) -> &[Arc<str>] {
+        &self.namespaces
+    }
+
+    /// Fully qualified slug of the namespace containing the project.
+    pub fn namespace(&self) -> String {
+        self.namespaces
+            .join(self.separator.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Separator between the namespaces in the slug.
+    pub fn separator(&self) -> char {
+        self.separator
+    }
+
+    /// Slug of the project this one is a symlink to.
+    pub fn links_to(&self) -> Option<&str> {
+        self.links_to.as_deref()
+    }
+
+    pub fn is_link(&self) -> bool {
+        self.link_path.is_some()
+    }
+
+    /// Slug of the project whose workspace member this one is.
+    pub fn parent(&self) -> Option<&str> {
+        self.parent.as_deref()
+    }
+
+    /// Slug of the main repository of a git worktree, if it was detected.
+    pub fn worktree_of(&self) -> Option<&str> {
+        self.worktree_of.as_deref()
+    }
+
+    /// Names the project is matched by exactly besides its slug, from its
+    /// project file and the configured aliases.
+    pub fn aliases(&self) -> &[Arc<str>] {
+        &self.aliases
+    }
+
+    pub fn add_alias(&mut self, alias: Arc<str>) {
+        if !self.aliases.contains(&alias) {
+            self.aliases.push(alias);
+        }
+    }
+
+    pub fn git(&self) -> Option<&GitInfo> {
+        self.git.as_ref()
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Path of the project with symlinks resolved, only known if the
+    /// project was reached through a symlink.
+    pub fn canonical_path(&self) -> Option<&Path> {
+        self.canonical.as_deref()
+    }
+
+    pub fn fmt_path(&self) -> String {
+        format!("{}", self.path.display())
+    }
+}
+
+/// Aliases from the project file of `project`.
+fn project_aliases(project: &Project) -> Vec<Arc<str>> {
+    project
+        .metadata
+        .aliases()
+        .iter()
+        .map(|alias| alias.as_str().into())
+        .collect()
+}
+
+impl From<&Project> for SlugPath {
+    fn from(project: &Project) -> Self {
+        SlugPath::new(
+            Vec::new(),
+            project,
+            SlugRules::default().namespace_separator(),
+        )
+    }
+}
+
+/// Problem with a single path that was skipped during detection.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Warning {
+    path: PathBuf,
+    kind: WarningKind,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum WarningKind {
+    PermissionDenied,
+    BrokenSymlink,
+    /// The symlink points to a directory that is already being scanned
+    SymlinkCycle,
+    /// The file name is not valid UTF-8
+    InvalidName,
+    /// The project file could not be read or parsed, it is ignored
+    InvalidProjectFile(String),
+    /// The ignore file could not be read or has invalid patterns, which
+    /// are skipped
+    InvalidIgnoreFile(String),
+    Io(String),
+}
+
+impl Warning {
+    fn new(path: PathBuf, kind: WarningKind) -> Self {
+        Warning { path, kind }
+    }
+
+    fn from_io(path: PathBuf, err: &std::io::Error) -> Self {
+        let kind = match err.kind() {
+            std::io::ErrorKind::PermissionDenied => WarningKind::PermissionDenied,
+            _ => WarningKind::Io(err.to_string()),
+        };
+        Warning { path, kind }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn kind(&self) -> &WarningKind {
+        &self.kind
+    }
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            WarningKind::PermissionDenied => {
+                write!(f, "{}: permission denied", self.path.display())
+            }
+            WarningKind::BrokenSymlink => write!(f, "{}: broken symlink", self.path.display()),
+            WarningKind::SymlinkCycle => {
+                write!(
+                    f,
+                    "{}: symlink points to a parent directory",
+                    self.path.display()
+                )
+            }
+            WarningKind::InvalidName => {
+                write!(f, "{}: name is not valid UTF-8", self.path.display())
+            }
+            WarningKind::InvalidProjectFile(reason) => {
+                write!(
+                    f,
+                    "{}: invalid project file: {}",
+                    self.path.display(),
+                    reason
+                )
+            }
+            WarningKind::InvalidIgnoreFile(reason) => {
+                write!(
+                    f,
+                    "{}: invalid ignore file: {}",
+                    self.path.display(),
+                    reason
+                )
+            }
+            WarningKind::Io(err) => write!(f, "{}: {}", self.path.display(), err),
+        }
+    }
+}
+
+/// Slug that is derived for more than one directory, e.g. for `My App`
+/// and `my-app` or for the same project in two roots.
+///
+/// The first directory keeps the slug, the others are renamed to
+/// `<slug>-2`, `<slug>-3` and so on, using the configured word separator.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Collision {
+    slug: Arc<str>,
+    paths: Vec<PathBuf>,
+    /// Slugs assigned to `paths`, in the same order
+    slugs: Vec<Arc<str>>,
+}
+
+impl Collision {
+    pub fn slug(&self) -> &str {
+        &self.slug
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Fully qualified slugs the directories were given, in the order of
+    /// [`Collision::paths`].
+    pub fn slugs(&self) -> &[Arc<str>] {
+        &self.slugs
+    }
+}
+
+impl std::fmt::Display for Collision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' is provided by multiple directories:", self.slug)?;
+        for (path, slug) in self.paths.iter().zip(&self.slugs) {
+            write!(f, "\n  - {} ({})", path.display(), slug)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RootNamespace {
+    info: Info,
+    items: Vec<NamespaceItem>,
+    collisions: Vec<Collision>,
+    warnings: Vec<Warning>,
+    /// Separator between the namespaces of a slug
+    #[serde(default = "default_separator")]
+    separator: char,
+    #[serde(skip)]
+    scanned_paths: Vec<PathBuf>,
+}
+
+fn default_separator() -> char {
+    SlugRules::default().namespace_separator()
+}
+
+impl RootNamespace {
+    pub fn new(name: Arc<str>, slug: Arc<str>, path: PathBuf) -> Self {
+        Self {
+            info: Info { name, slug, path },
+            items: Vec::new(),
+            collisions: Vec::new(),
+            warnings: Vec::new(),
+            separator: default_separator(),
+            scanned_paths: Vec::new(),
+        }
+    }
+
+    fn with_items(
+        info: Info,
+        items: Vec<NamespaceItem>,
+        collisions: Vec<Collision>,
+        warnings: Vec<Warning>,
+        separator: char,
+        scanned_paths: Vec<PathBuf>,
+    ) -> Self {
+        Self {
+            info,
+            items,
+            collisions,
+            warnings,
+            separator,
+            scanned_paths,
+        }
+    }
+
+    /// Separator between the namespaces of a slug.
+    pub fn separator(&self) -> char {
+        self.separator
+    }
+
+    /// Slugs that were derived for more than one directory, within a
+    /// namespace or while merging the project roots.
+    pub fn collisions(&self) -> &[Collision] {
+        &self.collisions
+    }
+
+    pub fn items(&self) -> &[NamespaceItem] {
+        &self.items
+    }
+
+    /// Paths that were skipped because they could not be read.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Number of projects in the whole tree, symlinks to other projects are not
+    /// counted.
+    pub fn project_count(&self) -> usize {
+        count_projects(&self.items)
+    }
+
+    /// Find the namespace with the fully qualified slug `slug`.
+    pub fn find_namespace(&self, slug: &str) -> Option<&SubNamespace> {
+        find_namespace(&self.items, "", slug, self.separator)
+    }
+
+    /// Fully qualified slugs of all namespaces that were truncated at the
+    /// maximum depth.
+    pub fn truncated_namespaces(&self) -> Vec<String> {
+        let mut slugs = Vec::new();
+        collect_truncated(&self.items, "", self.separator, &mut slugs);
+        slugs
+    }
+
+    /// Directories and files whose modification the detection result
+    /// depends on.
+    ///
+    /// Only available on a freshly detected tree, it is not serialized.
+    pub fn scanned_paths(&self) -> &[PathBuf] {
+        &self.scanned_paths
+    }
+
+    pub fn build_project_slugs(&self) -> Vec<SlugPath> {
+        let mut slug_paths = Vec::new();
+        for item in &self.items {
+            match item {
+                NamespaceItem::Project(project) => {
+                    slug_paths.extend(SlugPath::with_members(Vec::new(), project, self.separator));
+                }
+                NamespaceItem::Namespace(namespace) => {
+                    slug_paths.extend(namespace.build_project_slugs(&[], self.separator));
+                }
+            }
+        }
+
+        let targets: HashMap<Arc<Path>, Arc<str>> = slug_paths
+            .iter()
+            .filter(|slug_path| !slug_path.is_link())
+            .map(|slug_path| (slug_path.path.clone(), slug_path.slug.clone()))
+            .collect();
+        for slug_path in slug_paths.iter_mut() {
+            if let Some(link_path) = &slug_path.link_path {
+                slug_path.links_to = targets.get(link_path).cloned();
+            }
+            if let Some(main) = &slug_path.worktree_path {
+                slug_path.worktree_of = targets.get(main).cloned();
+            }
+        }
+
+        // Slugs always win over aliases
+        let slugs: HashSet<Arc<str>> = slug_paths
+            .iter()
+            .map(|slug_path| slug_path.slug.clone())
+            .collect();
+        for slug_path in slug_paths.iter_mut() {
+            slug_path.aliases.retain(|alias| !slugs.contains(alias));
+        }
+
+        slug_paths
+    }
+}
+
+impl std::fmt::Display for RootNamespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "RootNamespace: {}", self.info.path.display())?;
+        for item in &self.items {
+            match item {
+                NamespaceItem::Project(project) => {
+                    writeln!(f, "  - {}", project)?;
+                }
+                NamespaceItem::Namespace(namespace) => {
+                    writeln!(f, "  - {}", namespace)?;
+                    namespace.display_items(f, 4)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SubNamespace {
+    info: Info,
+    items: Vec<NamespaceItem>,
+    #[serde(default)]
+    truncated: bool,
+}
+
+impl SubNamespace {
+    pub fn new(name: Arc<str>, slug: Arc<str>, path: PathBuf) -> Self {
+        Self {
+            info: Info { name, slug, path },
+            items: Vec::new(),
+            truncated: false,
+        }
+    }
+
+    /// Slugs of all projects in this namespace, `namespaces` are the slugs
+    /// of the enclosing namespaces, joined by `separator`.
+    pub fn build_project_slugs(&self, namespaces: &[Arc<str>], separator: char) -> Vec<SlugPath> {
+        let mut slug_paths = Vec::new();
+        let mut my_namespaces = namespaces.to_vec();
+        my_namespaces.push(self.info.slug.clone());
+        for item in &self.items {
+            match item {
+                NamespaceItem::Project(project) => {
+                    slug_paths.extend(SlugPath::with_members(
+                        my_namespaces.clone(),
+                        project,
+                        separator,
+                    ));
+                }
+                NamespaceItem::Namespace(namespace) => {
+                    slug_paths.extend(namespace.build_project_slugs(&my_namespaces, separator));
+                }
+            }
+        }
+        slug_paths
+    }
+
+    fn with_items(info: Info, items: Vec<NamespaceItem>) -> Self {
+        Self {
+            info,
+            items,
+            truncated: false,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    pub fn slug(&self) -> &str {
+        &self.info.slug
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.info.path
+    }
+
+    pub fn items(&self) -> &[NamespaceItem] {
+        &self.items
+    }
+
+    /// Number of projects in this namespace and all nested namespaces.
+    pub fn project_count(&self) -> usize {
+        count_projects(&self.items)
+    }
+
+    /// Whether the subdirectories of this namespace were not scanned
+    /// because the maximum depth was reached.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    fn display_items(&self, f: &mut std::fmt::Formatter<'_>, indent: usize) -> std::fmt::Result {
+        let indent_str = " ".repeat(indent);
+        for item in &self.items {
+            match item {
+                NamespaceItem::Project(project) => {
+                    writeln!(f, "{}- {}", indent_str, project)?;
+                }
+                NamespaceItem::Namespace(namespace) => {
+                    writeln!(f, "{}- {}", indent_str, namespace)?;
+                    namespace.display_items(f, indent + 2)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for SubNamespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Namespace '{}' ({}): {}",
+            self.info.name,
+            self.info.slug,
+            self.info.path.display()
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum NamespaceItem {
+    Project(Project),
+    Namespace(SubNamespace),
+}
+
+impl NamespaceItem {
+    fn info(&self) -> &Info {
+        match self {
+            NamespaceItem::Project(project) => &project.info,
+            NamespaceItem::Namespace(namespace) => &namespace.info,
+        }
+    }
+}
+
+fn count_projects(items: &[NamespaceItem]) -> usize {
+    items
+        .iter()
+        .map(|item| match item {
+            NamespaceItem::Project(project) => usize::from(project.links_to.is_none()),
+            NamespaceItem::Namespace(namespace) => namespace.project_count(),
+        })
+        .sum()
+}
+
+fn collect_truncated(
+    items: &[NamespaceItem],
+    aggregated_slug: &str,
+    separator: char,
+    slugs: &mut Vec<String>,
+) {
+    for item in items {
+        let NamespaceItem::Namespace(namespace) = item else {
+            continue;
+        };
+        let my_slug = qualify(aggregated_slug, &namespace.info.slug, separator);
+
+        if namespace.truncated {
+            slugs.push(my_slug.clone());
+        }
+        collect_truncated(&namespace.items, &my_slug, separator, slugs);
+    }
+}
+
+fn find_namespace<'a>(
+    items: &'a [NamespaceItem],
+    aggregated_slug: &str,
+    slug: &str,
+    separator: char,
+) -> Option<&'a SubNamespace> {
+    items.iter().find_map(|item| {
+        let NamespaceItem::Namespace(namespace) = item else {
+            return None;
+        };
+        let my_slug = qualify(aggregated_slug, &namespace.info.slug, separator);
+
+        if my_slug == slug {
+            Some(namespace)
+        } else if slug.starts_with(&format!("{}{}", my_slug, separator)) {
+            find_namespace(&namespace.items, &my_slug, slug, separator)
+        } else {
+            None
+        }
+    })
+}
+
+/// Sort `items` and all nested items by name, so the tree does not depend
+/// on the order directories are listed in by the filesystem.
+///
+/// Names are only equal for directories of different roots, these keep
+/// the order of the roots.
+fn sort_items(items: &mut [NamespaceItem]) {
+    items.sort_by(|a, b| {
+        let (a, b) = (a.info(), b.info());
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    for item in items {
+        if let NamespaceItem::Namespace(namespace) = item {
+            sort_items(&mut namespace.items);
+        }
+    }
+}
+
+/// Merge `new_items` into `items`.
+///
+/// Namespaces with the same slug are merged recursively, every other
+/// item sharing a slug with an existing one is kept and renamed later by
+/// [`disambiguate`].
+fn merge_items(items: &mut Vec<NamespaceItem>, new_items: Vec<NamespaceItem>) {
+    for new_item in new_items {
+        let existing = items.iter_mut().find(|item| match (item, &new_item) {
+            (NamespaceItem::Namespace(existing), NamespaceItem::Namespace(namespace)) => {
+                existing.info.slug == namespace.info.slug
+            }
+            _ => false,
+        });
+
+        match (existing, new_item) {
+            (Some(NamespaceItem::Namespace(existing)), NamespaceItem::Namespace(namespace)) => {
+                existing.truncated |= namespace.truncated;
+                merge_items(&mut existing.items, namespace.items);
+            }
+            (_, new_item) => items.push(new_item),
+        }
+    }
+}
+
+/// Give all projects in `items` and their members unique slugs, and all
+/// namespaces as well, renamed items are reported in `collisions`.
+///
+/// Of the items sharing a slug, projects that are not symlinks to other
+/// projects win over links and directories named exactly like the slug win over
+/// directories whose name had to be converted. Remaining ties keep the
+/// order of `items`, i.e. the order of the roots, so the result is the
+/// same on every run.
+fn disambiguate(
+    items: &mut [NamespaceItem],
+    aggregated_slug: &str,
+    rules: &SlugRules,
+    collisions: &mut Vec<Collision>,
+) {
+    let qualify = |slug: &str| -> Arc<str> {
+        qualify(aggregated_slug, slug, rules.namespace_separator()).into()
+    };
+    let word_separator = rules.word_separator();
+
+    let mut projects = Vec::new();
+    let mut namespaces = Vec::new();
+    for item in items.iter_mut() {
+        match item {
+            NamespaceItem::Project(project) => {
+                projects.push((&mut project.info, project.links_to.is_some()))
+            }
+            NamespaceItem::Namespace(namespace) => namespaces.push((&mut namespace.info, false)),
+        }
+    }
+    rename_duplicates(&mut projects, &qualify, word_separator, collisions);
+    rename_duplicates(&mut namespaces, &qualify, word_separator, collisions);
+
+    for item in items {
+        match item {
+            NamespaceItem::Project(project) => {
+                disambiguate_members(project, &qualify(&project.info.slug), rules, collisions)
+            }
+            NamespaceItem::Namespace(namespace) => {
+                let slug = qualify(&namespace.info.slug);
+                disambiguate(&mut namespace.items, &slug, rules, collisions);
+            }
+        }
+    }
+}
+
+/// Give the members of `project`, whose fully qualified slug is `slug`,
+/// unique slugs.
+fn disambiguate_members(
+    project: &mut Project,
+    slug: &str,
+    rules: &SlugRules,
+    collisions: &mut Vec<Collision>,
+) {
+    let mut members: Vec<(&mut Info, bool)> = project
+        .members
+        .iter_mut()
+        .map(|member| (&mut member.info, false))
+        .collect();
+    let qualify =
+        |member: &str| -> Arc<str> { format!("{}{}{}", slug, MEMBER_SEPARATOR, member).into() };
+    rename_duplicates(&mut members, &qualify, rules.word_separator(), collisions);
+}
+
+/// Rename all but the preferred one of each group of `infos` sharing a
+/// slug to the first free `<slug>-<n>`, with `-` being the
+/// `word_separator`. The flag marks symlinks to other projects.
+///
+/// `qualify` turns a slug into the fully qualified one that is reported.
+fn rename_duplicates(
+    infos: &mut [(&mut Info, bool)],
+    qualify: &dyn Fn(&str) -> Arc<str>,
+    word_separator: char,
+    collisions: &mut Vec<Collision>,
+) {
+    let mut groups: Vec<(Arc<str>, Vec<usize>)> = Vec::new();
+    for (index, (info, _)) in infos.iter().enumerate() {
+        match groups.iter_mut().find(|(slug, _)| *slug == info.slug) {
+            Some((_, indices)) => indices.push(index),
+            None => groups.push((info.slug.clone(), vec![index])),
+        }
+    }
+
+    let mut taken: HashSet<Arc<str>> = groups.iter().map(|(slug, _)| slug.clone()).collect();
+    for (slug, mut indices) in groups.into_iter().filter(|(_, indices)| indices.len() > 1) {
+        indices.sort_by_key(|&index| {
+            let (info, is_link) = &infos[index];
+            let named_like_slug = info
+                .path
+                .file_name()
+                .is_some_and(|name| name.to_str() == Some(slug.as_ref()));
+            (*is_link, !named_like_slug, index)
+        });
+
+        let mut collision = Collision {
+            slug: qualify(&slug),
+            paths: Vec::new(),
+            slugs: Vec::new(),
+        };
+        let mut suffix = 2;
+        for (position, index) in indices.into_iter().enumerate() {
+            let info = &mut infos[index].0;
+            if position > 0 {
+                let renamed: Arc<str> = loop {
+                    let candidate: Arc<str> =
+                        format!("{}{}{}", slug, word_separator, suffix).into();
+                    suffix += 1;
+                    if !taken.contains(&candidate) {
+                        break candidate;
+                    }
+                };
+                taken.insert(renamed.clone());
+                info.slug = renamed;
+            }
+            collision.paths.push(info.path.clone());
+            collision.slugs.push(qualify(&info.slug));
+        }
+        collisions.push(collision);
+    }
+}
+
+/// Namespace trees for tests, built without scanning directories.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct TreeBuilder {
+    items: Vec<NamespaceItem>,
+}
+
+#[cfg(test)]
+impl TreeBuilder {
+    /// Add a project with the fully qualified `slug`, split into
+    /// namespaces at `.`. Its directory is named like the last segment.
+    pub(crate) fn project(self, slug: &str, kinds: &[&str]) -> Self {
+        let name = slug.rsplit('.').next().unwrap_or(slug).to_string();
+        self.project_in(slug, &name, kinds)
+    }
+
+    /// Add a project with the fully qualified `slug` in a directory called
+    /// `name`.
+    pub(crate) fn project_in(mut self, slug: &str, name: &str, kinds: &[&str]) -> Self {
+        let mut segments: Vec<&str> = slug.split('.').collect();
+        let project_slug = segments.pop().unwrap_or_default();
+        let namespace_path: PathBuf = std::iter::once("/").chain(segments.clone()).collect();
+
+        let project = Project::new(
+            name.into(),
+            project_slug.into(),
+            namespace_path.join(name),
+            kinds.iter().map(|&kind| kind.into()).collect(),
+        );
+        let mut item = NamespaceItem::Project(project);
+        for (depth, segment) in segments.iter().enumerate().rev() {
+            let path: PathBuf = std::iter::once("/")
+                .chain(segments[..=depth].iter().copied())
+                .collect();
+            let mut namespace = SubNamespace::new((*segment).into(), (*segment).into(), path);
+            namespace.items.push(item);
+            item = NamespaceItem::Namespace(namespace);
+        }
+        merge_items(&mut self.items, vec![item]);
+        self
+    }
+
+    pub(crate) fn build(self) -> RootNamespace {
+        let mut root = RootNamespace::new("Root".into(), "root".into(), "/".into());
+        root.items = self.items;
+        root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Slugs after renaming the duplicates of `entries`, given as path,
+    /// slug and whether the entry is a symlink to another project.
+    fn rename(entries: &[(&str, &str, bool)]) -> (Vec<String>, Vec<Collision>) {
+        let mut infos: Vec<Info> = entries
+            .iter()
+            .map(|&(path, slug, _)| Info {
+                name: Path::new(path)
+                    .file_name()
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .into(),
+                slug: slug.into(),
+                path: path.into(),
+            })
+            .collect();
+        let mut collisions = Vec::new();
+        let mut refs: Vec<(&mut Info, bool)> = infos
+            .iter_mut()
+            .zip(entries.iter().map(|&(_, _, is_link)| is_link))
+            .collect();
+        rename_duplicates(&mut refs, &|slug| slug.into(), '-', &mut collisions);
+
+        let slugs = infos.iter().map(|info| info.slug.to_string()).collect();
+        (slugs, collisions)
+    }
+
+    #[test]
+    fn exact_name_keeps_the_slug() {
+        let (slugs, collisions) = rename(&[
+            ("/a/My App", "my-app", false),
+            ("/b/my-app", "my-app", false),
+        ]);
+        assert_eq!(slugs, ["my-app-2", "my-app"]);
+
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].slug(), "my-app");
+        assert_eq!(
+            collisions[0].paths(),
+            [PathBuf::from("/b/my-app"), PathBuf::from("/a/My App")]
+        );
+        let collision_slugs: Vec<&str> = collisions[0].slugs().iter().map(AsRef::as_ref).collect();
+        assert_eq!(collision_slugs, ["my-app", "my-app-2"]);
+    }
+
+    #[test]
+    fn links_lose_before_names_are_compared() {
+        let (slugs, _) = rename(&[
+            ("/a/my-app", "my-app", true),
+            ("/b/My App", "my-app", false),
+        ]);
+        assert_eq!(slugs, ["my-app-2", "my-app"]);
+    }
+
+    #[test]
+    fn root_order_breaks_remaining_ties() {
+        let (slugs, collisions) = rename(&[
+            ("/a/my-app", "my-app", false),
+            ("/b/my-app", "my-app", false),
+            ("/c/my-app", "my-app", false),
+        ]);
+        assert_eq!(slugs, ["my-app", "my-app-2", "my-app-3"]);
+        assert_eq!(collisions.len(), 1);
+    }
+
+    #[test]
+    fn skips_suffixes_that_are_taken() {
+        let (slugs, _) = rename(&[
+            ("/a/my-app", "my-app", false),
+            ("/b/my-app", "my-app", false),
+            ("/c/my-app-2", "my-app-2", false),
+        ]);
+        assert_eq!(slugs, ["my-app", "my-app-3", "my-app-2"]);
+    }
+
+    #[test]
+    fn unique_slugs_are_kept() {
+        let (slugs, collisions) = rename(&[("/a/api", "api", false), ("/a/web", "web", false)]);
+        assert_eq!(slugs, ["api", "web"]);
+        assert!(collisions.is_empty());
+    }
+
+    #[test]
+    fn reports_collisions_with_qualified_slugs() {
+        let mut root = TreeBuilder::default()
+            .project_in("work.my-app", "My App", &[])
+            .project("work.my-app", &[])
+            .project("oss.my-app", &[])
+            .build();
+        let mut collisions = Vec::new();
+        disambiguate(&mut root.items, "", &SlugRules::default(), &mut collisions);
+        root.collisions = collisions;
+
+        let mut slugs: Vec<String> = root
+            .build_project_slugs()
+            .iter()
+            .map(|slug_path| slug_path.slug().to_string())
+            .collect();
+        slugs.sort();
+        assert_eq!(slugs, ["oss.my-app", "work.my-app", "work.my-app-2"]);
+
+        assert_eq!(root.collisions().len(), 1);
+        assert_eq!(root.collisions()[0].slug(), "work.my-app");
+        assert_eq!(
+            root.collisions()[0].paths(),
+            [PathBuf::from("/work/my-app"), PathBuf::from("/work/My App")]
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use super::{DirListing, SlugRules};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// Name of the per-project settings file.
+pub const PROJECT_FILE_NAME: &str = ".project.toml";
+
+/// Settings of a project, read from its [`PROJECT_FILE_NAME`].
+///
+/// ```toml
+/// slug = "pm"
+/// name = "Project Manager"
+/// aliases = ["projects"]
+/// tags = ["rust", "cli"]
+/// description = "Switch between projects"
+/// generator = "cargo"
+/// publisher = "crates.io"
+///
+/// [bookmarks]
+/// cli = "crates/cli"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ProjectFile {
+    /// Replaces the slug derived from the directory name
+    pub(crate) slug: Option<String>,
+    /// Replaces the directory name
+    pub(crate) name: Option<String>,
+    /// Additional slugs the project is matched by
+    #[serde(default)]
+    pub(crate) aliases: Vec<String>,
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+    pub(crate) description: Option<String>,
+    /// Preferred generator, e.g. `cargo` or `git`
+    pub(crate) generator: Option<String>,
+    /// Where the project is published, e.g. `crates.io`
+    pub(crate) publisher: Option<String>,
+    /// Directories inside the project, `cd slug:name` switches to them
+    #[serde(default)]
+    pub(crate) bookmarks: BTreeMap<String, PathBuf>,
+}
+
+impl ProjectFile {
+    /// Read the project file in the directory described by `listing`.
+    ///
+    /// `Ok(None)` if there is none, an error describes why the file is
+    /// invalid. Slugs must be valid single segments under `rules`. The file
+    /// is added to `scanned_paths` if it exists.
+    pub(crate) fn read(
+        listing: &DirListing,
+        rules: &SlugRules,
+        scanned_paths: &mut Vec<PathBuf>,
+    ) -> Result<Option<Self>, String> {
+        if !listing.contains(PROJECT_FILE_NAME) {
+            return Ok(None);
+        }
+        let path = listing.path().join(PROJECT_FILE_NAME);
+        let content = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+        scanned_paths.push(path);
+
+        let file: ProjectFile =
+            toml::from_str(&content).map_err(|err| err.message().to_string())?;
+        if let Some(slug) = &file.slug {
+            rules.validate_slug(slug)?;
+        }
+        for alias in &file.aliases {
+            rules.validate_slug(alias)?;
+        }
+        for (name, path) in &file.bookmarks {
+            rules.validate_slug(name)?;
+            if !is_inside(path) {
+                return Err(format!(
+                    "bookmark '{}' must be a relative path inside the project",
+                    name
+                ));
+            }
+        }
+        Ok(Some(file))
+    }
+}
+
+/// Whether the relative `path` stays inside the directory it is joined to,
+/// i.e. it is not absolute and has no `..` components.
+pub fn is_inside(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
@@ -0,0 +1,311 @@
+use crate::error::Error;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+
+/// Separates the slug of a project from the slugs of its sub-projects.
+pub const MEMBER_SEPARATOR: char = ':';
+
+/// Separates a project from a directory inside it, e.g. `cd api/src`.
+pub const PATH_SEPARATOR: char = '/';
+
+/// How slugs are derived from directory names.
+///
+/// ```yaml
+/// slugs:
+///   namespace-separator: "_"
+///   word-separator: "-"
+///   preserve-case: false
+///   transliterate: true
+///   strip-punctuation: true
+///   hidden-namespaces: [src, work.clients]
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SlugRules {
+    /// Joins the slugs of nested namespaces and projects
+    namespace_separator: char,
+    /// Replaces whitespace and separators in directory names
+    word_separator: char,
+    /// Keep upper case letters instead of lowercasing
+    preserve_case: bool,
+    /// Replace accented Latin letters with their ASCII base letters
+    transliterate: bool,
+    /// Remove ASCII punctuation other than `-` and `_`
+    strip_punctuation: bool,
+    /// Fully qualified slugs of namespaces that are left out of the slugs
+    /// below them
+    hidden_namespaces: BTreeSet<String>,
+}
+
+impl SlugRules {
+    pub fn with_namespace_separator(mut self, separator: char) -> Self {
+        self.namespace_separator = separator;
+        self
+    }
+
+    pub fn with_word_separator(mut self, separator: char) -> Self {
+        self.word_separator = separator;
+        self
+    }
+
+    pub fn with_preserve_case(mut self, preserve: bool) -> Self {
+        self.preserve_case = preserve;
+        self
+    }
+
+    pub fn with_transliterate(mut self, transliterate: bool) -> Self {
+        self.transliterate = transliterate;
+        self
+    }
+
+    pub fn with_strip_punctuation(mut self, strip: bool) -> Self {
+        self.strip_punctuation = strip;
+        self
+    }
+
+    /// Leave the namespace with the fully qualified slug `slug` out of the
+    /// slugs below it, its items are listed in the parent namespace.
+    ///
+    /// `slug` is the slug the namespace would have without any hidden
+    /// namespaces.
+    pub fn with_hidden_namespace(mut self, slug: String) -> Self {
+        self.hidden_namespaces.insert(slug);
+        self
+    }
+
+    /// Check that the separators can be told apart from each other and from
+    /// the characters of a slug.
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid = |reason: String| Err(Error::InvalidSlugRules(reason));
+        let separators = [
+            ("namespace separator", self.namespace_separator),
+            ("word separator", self.word_separator),
+        ];
+        for (name, separator) in separators {
+            if separator.is_alphanumeric() || separator.is_whitespace() {
+                return invalid(format!(
+                    "{} '{}' must not be alphanumeric or whitespace",
+                    name, separator
+                ));
+            }
+        }
+        if self.namespace_separator == self.word_separator {
+            return invalid("namespace and word separator must differ".to_string());
+        }
+        if self.namespace_separator == MEMBER_SEPARATOR || self.word_separator == MEMBER_SEPARATOR {
+            return invalid(format!(
+                "'{}' is reserved for sub-projects",
+                MEMBER_SEPARATOR
+            ));
+        }
+        if self.namespace_separator == PATH_SEPARATOR || self.word_separator == PATH_SEPARATOR {
+            return invalid(format!(
+                "'{}' is reserved for directories inside projects",
+                PATH_SEPARATOR
+            ));
+        }
+        Ok(())
+    }
+
+    /// Separator between the namespaces of a slug.
+    pub fn namespace_separator(&self) -> char {
+        self.namespace_separator
+    }
+
+    /// Separator between the words of a single slug segment.
+    pub fn word_separator(&self) -> char {
+        self.word_separator
+    }
+
+    pub fn is_hidden(&self, namespace_slug: &str) -> bool {
+        self.hidden_namespaces.contains(namespace_slug)
+    }
+
+    /// Slug of a directory called `name`.
+    ///
+    /// Whitespace, the namespace and member separators and `/` are
+    /// replaced with the word separator, so the slug is a single segment.
+    /// Leading and trailing word separators are removed unless nothing
+    /// else is left. Punctuation is kept in names consisting only of
+    /// punctuation, their slug would be empty otherwise.
+    pub fn slugify(&self, name: &str) -> String {
+        let slug = self.slugify_with(name, self.strip_punctuation);
+        if slug.is_empty() && !name.is_empty() {
+            self.slugify_with(name, false)
+        } else {
+            slug
+        }
+    }
+
+    fn slugify_with(&self, name: &str, strip_punctuation: bool) -> String {
+        let mut slug = String::with_capacity(name.len());
+        for c in name.chars() {
+            let transliterated = if self.transliterate {
+                transliterate(c)
+            } else {
+                None
+            };
+            let mut buffer = [0; 4];
+            let replacement: &str = match transliterated {
+                Some(ascii) => ascii,
+                None => c.encode_utf8(&mut buffer),
+            };
+
+            for c in replacement.chars() {
+                if c.is_whitespace()
+                    || c == self.namespace_separator
+                    || c == MEMBER_SEPARATOR
+                    || c == PATH_SEPARATOR
+                {
+                    slug.push(self.word_separator);
+                } else if strip_punctuation
+                    && c.is_ascii_punctuation()
+                    && !matches!(c, '-' | '_')
+                    && c != self.word_separator
+                {
+                    continue;
+                } else if self.preserve_case {
+                    slug.push(c);
+                } else {
+                    slug.extend(c.to_lowercase());
+                }
+            }
+        }
+
+        let trimmed = slug.trim_matches(self.word_separator);
+        if trimmed.is_empty() {
+            slug
+        } else {
+            trimmed.to_string()
+        }
+    }
+
+    /// Whether `slug`, e.g. from a project file, is a single segment.
+    pub(crate) fn validate_slug(&self, slug: &str) -> Result<(), String> {
+        if slug.is_empty() {
+            return Err("slugs must not be empty".to_string());
+        }
+        if slug.contains([self.namespace_separator, MEMBER_SEPARATOR, PATH_SEPARATOR])
+            || slug.contains(char::is_whitespace)
+        {
+            return Err(format!(
+                "slug '{}' must not contain '{}', '{}', '/' or whitespace",
+                slug, self.namespace_separator, MEMBER_SEPARATOR
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for SlugRules {
+    fn default() -> Self {
+        SlugRules {
+            namespace_separator: '.',
+            word_separator: '-',
+            preserve_case: false,
+            transliterate: false,
+            strip_punctuation: false,
+            hidden_namespaces: BTreeSet::new(),
+        }
+    }
+}
+
+/// ASCII replacement of accented and special Latin letters.
+fn transliterate(c: char) -> Option<&'static str> {
+    let ascii = match c {
+        'À'..='Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+        'Æ' => "AE",
+        'æ' => "ae",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'Ď' | 'Đ' | 'Ð' => "D",
+        'ď' | 'đ' | 'ð' => "d",
+        'È'..='Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'Ĥ' | 'Ħ' => "H",
+        'ĥ' | 'ħ' => "h",
+        'Ì'..='Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'Ĵ' => "J",
+        'ĵ' => "j",
+        'Ķ' => "K",
+        'ķ' => "k",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' => "N",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'Ò'..='Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'Œ' => "OE",
+        'œ' => "oe",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'Ţ' | 'Ť' | 'Ŧ' => "T",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'Þ' => "TH",
+        'þ' => "th",
+        'Ù'..='Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'Ŵ' => "W",
+        'ŵ' => "w",
+        'Ý' | 'Ŷ' | 'Ÿ' => "Y",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    };
+    Some(ascii)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugify_replaces_namespace_separator() {
+        let rules = SlugRules::default();
+        assert_eq!(rules.slugify("example.com"), "example-com");
+        assert_eq!(rules.slugify("My App"), "my-app");
+        assert_eq!(rules.slugify(" api:v2 "), "api-v2");
+    }
+
+    #[test]
+    fn slugify_transliterates() {
+        let rules = SlugRules::default().with_transliterate(true);
+        assert_eq!(rules.slugify("Café"), "cafe");
+        assert_eq!(rules.slugify("Straße"), "strasse");
+        assert_eq!(SlugRules::default().slugify("Café"), "café");
+    }
+
+    #[test]
+    fn slugify_strips_punctuation() {
+        let rules = SlugRules::default().with_strip_punctuation(true);
+        assert_eq!(rules.slugify("it's (old)"), "its-old");
+        assert_eq!(rules.slugify("snake_case-name"), "snake_case-name");
+        assert_eq!(rules.slugify("!!!"), "!!!");
+        assert_eq!(rules.slugify("---"), "---");
+    }
+
+    #[test]
+    fn slugify_preserves_case() {
+        let rules = SlugRules::default().with_preserve_case(true);
+        assert_eq!(rules.slugify("My App"), "My-App");
+        let rules = rules.with_transliterate(true);
+        assert_eq!(rules.slugify("Ærø"), "AEro");
+    }
+
+    #[test]
+    fn slugify_uses_configured_separators() {
+        let rules = SlugRules::default()
+            .with_namespace_separator('_')
+            .with_word_separator('.');
+        assert_eq!(rules.slugify("example.com"), "example.com");
+        assert_eq!(rules.slugify("my_app"), "my.app");
+    }
+}
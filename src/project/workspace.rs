@@ -0,0 +1,530 @@
+use super::{DirListing, ProjectKind};
+use globset::{GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// Maximum number of directory levels walked to expand a `**` pattern.
+const MAX_GLOB_DEPTH: usize = 8;
+
+/// Directory of a workspace member, listed in a manifest of its project.
+pub(crate) struct Member {
+    pub(crate) path: PathBuf,
+    /// Kind of the workspace that lists the member
+    pub(crate) kind: ProjectKind,
+}
+
+/// Members of the workspaces defined in a project directory.
+#[derive(Default)]
+pub(crate) struct Workspace {
+    pub(crate) members: Vec<Member>,
+    /// Manifests and directories the members were found in
+    pub(crate) scanned_paths: Vec<PathBuf>,
+}
+
+impl Workspace {
+    /// Read the workspace manifests in `listing`: Cargo workspaces, npm and
+    /// pnpm workspaces, `go.work` files and meson subprojects.
+    pub(crate) fn read(listing: &DirListing) -> Self {
+        let mut workspace = Workspace::default();
+        let dir = listing.path();
+
+        if listing.contains("Cargo.toml") {
+            workspace.read_cargo(dir);
+        }
+        if listing.contains("package.json") {
+            workspace.read_npm(dir);
+        }
+        if listing.contains("pnpm-workspace.yaml") {
+            workspace.read_pnpm(dir);
+        }
+        if listing.contains("go.work") {
+            workspace.read_go(dir);
+        }
+        if listing.contains("meson.build") && listing.contains("subprojects") {
+            workspace.read_meson(dir);
+        }
+
+        workspace
+    }
+
+    fn read_cargo(&mut self, dir: &Path) {
+        #[derive(Deserialize)]
+        struct Manifest {
+            workspace: Option<CargoWorkspace>,
+        }
+        #[derive(Deserialize)]
+        struct CargoWorkspace {
+            #[serde(default)]
+            members: Vec<String>,
+            #[serde(default)]
+            exclude: Vec<String>,
+        }
+
+        let manifest_path = dir.join("Cargo.toml");
+        let Some(manifest) = fs::read_to_string(&manifest_path)
+            .ok()
+            .and_then(|content| toml::from_str::<Manifest>(&content).ok())
+        else {
+            return;
+        };
+        self.scanned_paths.push(manifest_path);
+
+        if let Some(workspace) = manifest.workspace {
+            self.add_members(
+                dir,
+                &workspace.members,
+                &workspace.exclude,
+                "Cargo.toml",
+                ProjectKind::Cargo,
+            );
+        }
+    }
+
+    fn read_npm(&mut self, dir: &Path) {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Workspaces {
+            Patterns(Vec<String>),
+            Object {
+                #[serde(default)]
+                packages: Vec<String>,
+            },
+        }
+        #[derive(Deserialize)]
+        struct Manifest {
+            workspaces: Option<Workspaces>,
+        }
+
+        let manifest_path = dir.join("package.json");
+        let Some(manifest) = fs::read(&manifest_path)
+            .ok()
+            .and_then(|content| serde_json::from_slice::<Manifest>(&content).ok())
+        else {
+            return;
+        };
+        self.scanned_paths.push(manifest_path);
+
+        let patterns = match manifest.workspaces {
+            Some(Workspaces::Patterns(patterns)) => patterns,
+            Some(Workspaces::Object { packages }) => packages,
+            None => return,
+        };
+        self.add_members(dir, &patterns, &[], "package.json", ProjectKind::Node);
+    }
+
+    fn read_pnpm(&mut self, dir: &Path) {
+        #[derive(Deserialize)]
+        struct Manifest {
+            #[serde(default)]
+            packages: Vec<String>,
+        }
+
+        let manifest_path = dir.join("pnpm-workspace.yaml");
+        let Some(manifest) = fs::read_to_string(&manifest_path)
+            .ok()
+            .and_then(|content| serde_yaml::from_str::<Manifest>(&content).ok())
+        else {
+            return;
+        };
+        self.scanned_paths.push(manifest_path);
+
+        self.add_members(
+            dir,
+            &manifest.packages,
+            &[],
+            "package.json",
+            ProjectKind::Node,
+        );
+    }
+
+    fn read_go(&mut self, dir: &Path) {
+        let manifest_path = dir.join("go.work");
+        let Ok(content) = fs::read_to_string(&manifest_path) else {
+            return;
+        };
+        self.scanned_paths.push(manifest_path);
+
+        for module in go_work_modules(&content) {
+            let path = dir.join(module);
+            if path.is_dir() {
+                self.push(dir, path, ProjectKind::Go);
+            }
+        }
+    }
+
+    /// Subdirectories of `subprojects/` with their own `meson.build`,
+    /// wrap files and the package cache are skipped.
+    fn read_meson(&mut self, dir: &Path) {
+        let subprojects = dir.join("subprojects");
+        let Ok(read_dir) = subprojects.read_dir() else {
+            return;
+        };
+        self.scanned_paths.push(subprojects);
+
+        let mut paths: Vec<PathBuf> = read_dir
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.join("meson.build").is_file())
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            self.push(dir, path, ProjectKind::Meson);
+        }
+    }
+
+    /// Add the directories containing a `manifest` that match the glob
+    /// `patterns` but none of the `excludes`, patterns starting with `!`
+    /// are excludes as well.
+    fn add_members(
+        &mut self,
+        dir: &Path,
+        patterns: &[String],
+        excludes: &[String],
+        manifest: &str,
+        kind: ProjectKind,
+    ) {
+        let mut exclude_builder = GlobSetBuilder::new();
+        let mut includes = Vec::new();
+        for pattern in patterns {
+            match pattern.strip_prefix('!') {
+                Some(exclude) => add_glob(&mut exclude_builder, exclude),
+                None => includes.push(normalize(pattern)),
+            }
+        }
+        for exclude in excludes {
+            add_glob(&mut exclude_builder, exclude);
+        }
+        let excludes = exclude_builder.build().unwrap_or_else(|_| GlobSet::empty());
+
+        let mut paths = Vec::new();
+        for pattern in includes {
+            self.expand(dir, pattern, &mut paths);
+        }
+        paths.sort();
+        paths.dedup();
+
+        for path in paths {
+            let excluded = path
+                .strip_prefix(dir)
+                .is_ok_and(|relative| excludes.is_match(relative));
+            if !excluded && path.join(manifest).is_file() {
+                self.push(dir, path, kind.clone());
+            }
+        }
+    }
+
+    /// Collect the directories below `dir` matching `pattern` into `paths`.
+    fn expand(&mut self, dir: &Path, pattern: &str, paths: &mut Vec<PathBuf>) {
+        if !pattern.contains(['*', '?', '[', '{']) {
+            let path = dir.join(pattern);
+            if path.is_dir() {
+                paths.push(path);
+            }
+            return;
+        }
+
+        let Ok(glob) = GlobBuilder::new(pattern).literal_separator(true).build() else {
+            return;
+        };
+
+        // Only walk below the literal leading components of the pattern.
+        let components: Vec<&str> = pattern.split('/').collect();
+        let literal = components
+            .iter()
+            .take_while(|component| !component.contains(['*', '?', '[', '{']))
+            .count();
+        let base = components[..literal]
+            .iter()
+            .fold(dir.to_path_buf(), |path, component| path.join(component));
+        let depth = if pattern.contains("**") {
+            MAX_GLOB_DEPTH
+        } else {
+            components.len() - literal
+        };
+
+        self.walk(dir, &base, &glob.compile_matcher(), depth, paths);
+    }
+
+    fn walk(
+        &mut self,
+        root: &Path,
+        dir: &Path,
+        matcher: &GlobMatcher,
+        depth: usize,
+        paths: &mut Vec<PathBuf>,
+    ) {
+        if depth == 0 {
+            return;
+        }
+        let Ok(read_dir) = dir.read_dir() else {
+            return;
+        };
+        self.scanned_paths.push(dir.to_path_buf());
+
+        for entry in read_dir.flatten() {
+            let name = entry.file_name();
+            if name.to_string_lossy().starts_with('.') || name == "node_modules" {
+                continue;
+            }
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            if path
+                .strip_prefix(root)
+                .is_ok_and(|relative| matcher.is_match(relative))
+            {
+                paths.push(path.clone());
+            }
+            self.walk(root, &path, matcher, depth - 1, paths);
+        }
+    }
+
+    /// Add `path` unless it is the workspace directory itself, outside of
+    /// it or already a member.
+    fn push(&mut self, dir: &Path, path: PathBuf, kind: ProjectKind) {
+        let path = normalize_path(&path);
+        if path == dir
+            || !path.starts_with(dir)
+            || self.members.iter().any(|member| member.path == path)
+        {
+            return;
+        }
+        self.members.push(Member { path, kind });
+    }
+}
+
+fn add_glob(builder: &mut GlobSetBuilder, pattern: &str) {
+    if let Ok(glob) = GlobBuilder::new(normalize(pattern))
+        .literal_separator(true)
+        .build()
+    {
+        builder.add(glob);
+    }
+}
+
+/// Strip a leading `./` and trailing slashes.
+fn normalize(pattern: &str) -> &str {
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+    pattern.trim_end_matches('/')
+}
+
+/// Resolve `.` and `..` components lexically, `./` and `.` are common in
+/// `go.work` files and `../shared` leaves the workspace.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Modules of the `use` directives in a `go.work` file, either a single
+/// `use ./module` or a `use ( ... )` block.
+fn go_work_modules(content: &str) -> Vec<&str> {
+    let mut modules = Vec::new();
+    let mut in_use_block = false;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        if in_use_block {
+            if line == ")" {
+                in_use_block = false;
+            } else if !line.is_empty() {
+                modules.push(unquote(line));
+            }
+        } else if let Some(rest) = line.strip_prefix("use") {
+            match rest.trim() {
+                "(" => in_use_block = true,
+                module if rest.starts_with([' ', '\t']) => modules.push(unquote(module)),
+                _ => {}
+            }
+        }
+    }
+    modules
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim_matches(|c| c == '"' || c == '`')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Tree(tempfile::TempDir);
+
+    impl Tree {
+        fn new() -> Self {
+            Tree(tempfile::tempdir().unwrap())
+        }
+
+        fn file(self, path: &str, content: &str) -> Self {
+            let path = self.0.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+            self
+        }
+
+        fn dir(self, path: &str) -> Self {
+            fs::create_dir_all(self.0.path().join(path)).unwrap();
+            self
+        }
+
+        /// Members of the workspace in `repo`, relative to it.
+        fn members(&self, kind: ProjectKind) -> Vec<String> {
+            let dir = self.0.path().join("repo");
+            let workspace = Workspace::read(&DirListing::read(&dir).unwrap());
+            workspace
+                .members
+                .iter()
+                .map(|member| {
+                    assert_eq!(member.kind, kind);
+                    let relative = member.path.strip_prefix(&dir).unwrap();
+                    relative.to_string_lossy().into_owned()
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn cargo_members_and_excludes() {
+        let tree = Tree::new()
+            .file(
+                "repo/Cargo.toml",
+                "[workspace]\nmembers = [\"crates/*\", \"tools/cli/\"]\nexclude = [\"crates/skip\"]\n",
+            )
+            .file("repo/crates/a/Cargo.toml", "")
+            .file("repo/crates/b/Cargo.toml", "")
+            .file("repo/crates/skip/Cargo.toml", "")
+            .dir("repo/crates/no-manifest")
+            .file("repo/tools/cli/Cargo.toml", "");
+
+        assert_eq!(
+            tree.members(ProjectKind::Cargo),
+            ["crates/a", "crates/b", "tools/cli"]
+        );
+    }
+
+    #[test]
+    fn cargo_without_workspace() {
+        let tree = Tree::new()
+            .file("repo/Cargo.toml", "[package]\nname = \"repo\"\n")
+            .file("repo/crates/a/Cargo.toml", "");
+
+        assert!(tree.members(ProjectKind::Cargo).is_empty());
+    }
+
+    #[test]
+    fn npm_workspaces_with_negated_patterns() {
+        let tree = Tree::new()
+            .file(
+                "repo/package.json",
+                r#"{"workspaces": ["packages/*", "!packages/private"]}"#,
+            )
+            .file("repo/packages/ui/package.json", "{}")
+            .file("repo/packages/private/package.json", "{}");
+
+        assert_eq!(tree.members(ProjectKind::Node), ["packages/ui"]);
+    }
+
+    #[test]
+    fn npm_workspaces_object() {
+        let tree = Tree::new()
+            .file(
+                "repo/package.json",
+                r#"{"workspaces": {"packages": ["./app"]}}"#,
+            )
+            .file("repo/app/package.json", "{}");
+
+        assert_eq!(tree.members(ProjectKind::Node), ["app"]);
+    }
+
+    #[test]
+    fn pnpm_recursive_globs_skip_hidden_and_node_modules() {
+        let tree = Tree::new()
+            .file("repo/pnpm-workspace.yaml", "packages:\n  - 'apps/**'\n")
+            .file("repo/apps/web/package.json", "{}")
+            .file("repo/apps/group/admin/package.json", "{}")
+            .file("repo/apps/.cache/package.json", "{}")
+            .file("repo/apps/web/node_modules/dep/package.json", "{}");
+
+        assert_eq!(
+            tree.members(ProjectKind::Node),
+            ["apps/group/admin", "apps/web"]
+        );
+    }
+
+    #[test]
+    fn go_work_directives() {
+        let content = "go 1.22\n\nuse ./tools // single\nuse (\n\t./api\n\t\"./web\"\n\n)\nuser ./not-a-directive\n";
+        assert_eq!(go_work_modules(content), ["./tools", "./api", "./web"]);
+    }
+
+    #[test]
+    fn go_work_members() {
+        let tree = Tree::new()
+            .file(
+                "repo/go.work",
+                "use (\n\t.\n\t./api\n\t./missing\n\t../shared\n)\n",
+            )
+            .file("repo/api/go.mod", "")
+            .file("shared/go.mod", "");
+
+        assert_eq!(tree.members(ProjectKind::Go), ["api"]);
+    }
+
+    #[test]
+    fn meson_subprojects() {
+        let tree = Tree::new()
+            .file("repo/meson.build", "")
+            .file("repo/subprojects/zlib/meson.build", "")
+            .file("repo/subprojects/zlib.wrap", "")
+            .dir("repo/subprojects/packagecache");
+
+        assert_eq!(tree.members(ProjectKind::Meson), ["subprojects/zlib"]);
+    }
+
+    #[test]
+    fn patterns_do_not_leave_the_workspace() {
+        let tree = Tree::new()
+            .file(
+                "repo/Cargo.toml",
+                "[workspace]\nmembers = [\"../outside\", \"../outside/*\", \"crates/../../outside/a\", \"crates/a\"]\n",
+            )
+            .file("repo/crates/a/Cargo.toml", "")
+            .file("outside/Cargo.toml", "")
+            .file("outside/a/Cargo.toml", "");
+
+        assert_eq!(tree.members(ProjectKind::Cargo), ["crates/a"]);
+    }
+
+    #[test]
+    fn single_star_does_not_cross_directories() {
+        let tree = Tree::new()
+            .file("repo/Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n")
+            .file("repo/crates/a/Cargo.toml", "")
+            .file("repo/crates/group/b/Cargo.toml", "");
+
+        assert_eq!(tree.members(ProjectKind::Cargo), ["crates/a"]);
+    }
+
+    #[test]
+    fn normalizes_paths_lexically() {
+        assert_eq!(
+            normalize_path(Path::new("/repo/./crates/../api")),
+            Path::new("/repo/api")
+        );
+        assert_eq!(normalize("./crates/*/"), "crates/*");
+    }
+}
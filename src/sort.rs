@@ -0,0 +1,72 @@
+use crate::data::history::History;
+use crate::project::{git, SlugPath};
+use rayon::prelude::*;
+use std::{cmp::Ordering, collections::HashMap, fs, path::PathBuf, time::SystemTime};
+
+/// Order of listed projects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// Project name, case insensitive
+    Name,
+    /// Fully qualified slug
+    Slug,
+    /// Most recently modified project directory first
+    Mtime,
+    /// Most recent commit first, projects without git history last
+    LastCommit,
+    /// Most frequently and recently visited with `cd` first
+    Frecency,
+}
+
+/// Sort `slug_paths` by `key`, ties are broken by slug so the order is
+/// the same on every machine.
+///
+/// `history` is only used for [`SortKey::Frecency`].
+pub fn sort(slug_paths: &mut [SlugPath], key: SortKey, history: &History) {
+    match key {
+        SortKey::Name => slug_paths.sort_by(|a, b| {
+            a.name()
+                .to_lowercase()
+                .cmp(&b.name().to_lowercase())
+                .then_with(|| by_slug(a, b))
+        }),
+        SortKey::Slug => slug_paths.sort_by(by_slug),
+        SortKey::Mtime => sort_by_time(slug_paths, |slug_path| {
+            fs::metadata(slug_path.path())
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        }),
+        SortKey::LastCommit => {
+            // Every lookup runs git, so they run in parallel up front
+            let times: HashMap<PathBuf, SystemTime> = slug_paths
+                .par_iter()
+                .filter_map(|slug_path| {
+                    let time = git::last_commit_time(slug_path.path())?;
+                    Some((slug_path.path().to_path_buf(), time))
+                })
+                .collect();
+            sort_by_time(slug_paths, |slug_path| times.get(slug_path.path()).copied())
+        }
+        SortKey::Frecency => slug_paths.sort_by(|a, b| {
+            history
+                .frecency(b.path())
+                .total_cmp(&history.frecency(a.path()))
+                .then_with(|| by_slug(a, b))
+        }),
+    }
+}
+
+fn by_slug(a: &SlugPath, b: &SlugPath) -> Ordering {
+    a.slug().cmp(b.slug()).then_with(|| a.path().cmp(b.path()))
+}
+
+/// Sort newest first, projects without a time last.
+fn sort_by_time(slug_paths: &mut [SlugPath], time: impl Fn(&SlugPath) -> Option<SystemTime>) {
+    slug_paths.sort_by_cached_key(|slug_path| {
+        (
+            std::cmp::Reverse(time(slug_path)),
+            slug_path.slug().to_string(),
+            slug_path.path().to_path_buf(),
+        )
+    });
+}